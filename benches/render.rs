@@ -0,0 +1,182 @@
+use colored::{control, Color, ColorSupport, ColoredString, Colorize, Renderer, Styles};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Write};
+
+/// Writes the string into a reused buffer, as a logger would, so that only
+/// the allocations of the rendering itself are measured.
+fn render(c: &mut Criterion, name: &str, string: &impl Display) {
+    let mut buffer = String::with_capacity(64 * 1024);
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            buffer.clear();
+            write!(buffer, "{}", black_box(string)).unwrap();
+            black_box(buffer.len())
+        });
+    });
+}
+
+/// The rendering of colored 3.0.0, building `String`s for the codes and for
+/// the text with its nested resets escaped, to compare with.
+struct Baseline<'a>(&'a ColoredString);
+
+impl Baseline<'_> {
+    fn color_code(color: Color, background: bool) -> Cow<'static, str> {
+        const BASIC: [Color; 16] = [
+            Color::Black,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::White,
+            Color::BrightBlack,
+            Color::BrightRed,
+            Color::BrightGreen,
+            Color::BrightYellow,
+            Color::BrightBlue,
+            Color::BrightMagenta,
+            Color::BrightCyan,
+            Color::BrightWhite,
+        ];
+        const FOREGROUND: [&str; 16] = [
+            "30", "31", "32", "33", "34", "35", "36", "37", "90", "91", "92", "93", "94", "95",
+            "96", "97",
+        ];
+        const BACKGROUND: [&str; 16] = [
+            "40", "41", "42", "43", "44", "45", "46", "47", "100", "101", "102", "103", "104",
+            "105", "106", "107",
+        ];
+        let layer = if background { 48 } else { 38 };
+        match color {
+            Color::AnsiColor(code) => format!("{layer};5;{code}").into(),
+            Color::TrueColor { r, g, b } => {
+                // the support was checked on every render, the truecolor is
+                // kept as the benchmarks set it
+                let _ = black_box(std::env::var("COLORTERM"));
+                format!("{layer};2;{r};{g};{b}").into()
+            }
+            color => {
+                let index = BASIC.iter().position(|&basic| basic == color).unwrap();
+                let codes = if background { BACKGROUND } else { FOREGROUND };
+                codes[index].into()
+            }
+        }
+    }
+
+    fn compute_style(&self) -> String {
+        const STYLES: [(Styles, &str); 8] = [
+            (Styles::Bold, "1"),
+            (Styles::Dimmed, "2"),
+            (Styles::Italic, "3"),
+            (Styles::Underline, "4"),
+            (Styles::Blink, "5"),
+            (Styles::Reversed, "7"),
+            (Styles::Hidden, "8"),
+            (Styles::Strikethrough, "9"),
+        ];
+        let string = self.0;
+        let mut res = String::from("\x1B[");
+        let styles: Vec<&str> = STYLES
+            .iter()
+            .filter(|(style, _)| string.style.contains(*style))
+            .map(|(_, code)| *code)
+            .collect();
+        let mut has_wrote = !styles.is_empty();
+        res.push_str(&styles.join(";"));
+        if let Some(bgcolor) = string.bgcolor {
+            if has_wrote {
+                res.push(';');
+            }
+            res.push_str(&Self::color_code(bgcolor, true));
+            has_wrote = true;
+        }
+        if let Some(fgcolor) = string.fgcolor {
+            if has_wrote {
+                res.push(';');
+            }
+            res.push_str(&Self::color_code(fgcolor, false));
+        }
+        res.push('m');
+        res
+    }
+
+    #[allow(clippy::explicit_counter_loop)]
+    fn escape_inner_reset_sequences(&self) -> String {
+        let reset = "\x1B[0m";
+        let style = self.compute_style();
+        let matches: Vec<usize> = self
+            .0
+            .input
+            .match_indices(reset)
+            .map(|(idx, _)| idx)
+            .collect();
+        let mut input = self.0.input.clone();
+        input.reserve(matches.len() * style.len());
+        for (idx_in_matches, offset) in matches.into_iter().enumerate() {
+            let mut offset = offset + reset.len() + idx_in_matches * style.len();
+            for cchar in style.chars() {
+                input.insert(offset, cchar);
+                offset += 1;
+            }
+        }
+        input
+    }
+}
+
+impl Display for Baseline<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_plain() {
+            return <String as Display>::fmt(&self.0.input, f);
+        }
+        let escaped_input = self.escape_inner_reset_sequences();
+        f.write_str(&self.compute_style())?;
+        escaped_input.fmt(f)?;
+        f.write_str("\x1B[0m")
+    }
+}
+
+fn display(c: &mut Criterion) {
+    control::set_override(true);
+    // the colors are written as they are, without being downgraded
+    control::set_color_support(ColorSupport::TrueColor);
+
+    let nested = format!("a {} b", "nested".blue()).repeat(100);
+    let strings = [
+        ("plain", "a plain log line".normal()),
+        ("basic", "error".red().on_black().bold()),
+        ("ansi256", "warning".color(Color::AnsiColor(208))),
+        ("truecolor", "info".truecolor(0, 136, 255).italic()),
+        ("nested_resets", nested.red().underline()),
+    ];
+    for (name, string) in &strings {
+        assert_eq!(Baseline(string).to_string(), string.to_string(), "{name}");
+        render(c, name, string);
+        render(c, &format!("baseline/{name}"), &Baseline(string));
+    }
+
+    let gradient = "a gradient over a whole log line".gradient([Color::Red, Color::Blue]);
+    render(c, "gradient", &gradient);
+
+    let spans = [
+        "error".red().bold(),
+        "[E0308]".red(),
+        ": mismatched types".bold(),
+        " in main.rs".normal(),
+    ];
+    let mut buffer = String::with_capacity(1024);
+    c.bench_function("renderer", |b| {
+        b.iter(|| {
+            buffer.clear();
+            let mut renderer = Renderer::new(&mut buffer);
+            for span in black_box(&spans) {
+                renderer.write(span).unwrap();
+            }
+            renderer.finish().unwrap();
+        });
+    });
+}
+
+criterion_group!(benches, display);
+criterion_main!(benches);
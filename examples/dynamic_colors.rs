@@ -9,6 +9,12 @@ fn main() {
     "white string".color("zorglub");
 
     // the safer way via a Result
-    let color_res = "zorglub".parse(); // <- this returns a Result<Color, ()>
+    let color_res = "zorglub".parse(); // <- this returns a Result<Color, ColorParseError>
     "red string".color(color_res.unwrap_or(Color::Red));
+
+    // or directly when coloring
+    match "zorglub string".try_color("zorglub") {
+        Ok(colored) => println!("{colored}"),
+        Err(error) => eprintln!("{error}"),
+    }
 }
@@ -0,0 +1,572 @@
+//! Parsing of text containing ANSI escape sequences, e.g. the colored output
+//! of another program, back into [`ColoredString`]s.
+//!
+//! ```rust
+//! use colored::{ansi, Color, Colorize};
+//! let parsed = ansi::parse("\x1b[1;31mError:\x1b[22m not found\x1b[0m");
+//! assert_eq!(parsed.segments, ["Error:".red().bold(), " not found".red()]);
+//! ```
+
+use crate::{Color, ColoredString, Styles, UnderlineStyle};
+use std::{borrow::Cow, ops::Range};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// The result of [`parse`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Parsed {
+    /// The text, split into runs of the same colors and style. The escape
+    /// sequences are removed.
+    pub segments: Vec<ColoredString>,
+    /// The escape sequences that were skipped, as they don't set colors,
+    /// style or hyperlinks.
+    pub skipped: Vec<Sequence>,
+}
+
+/// An escape sequence skipped by [`parse`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Sequence {
+    /// The byte offset of the sequence in the parsed text.
+    pub offset: usize,
+    /// The sequence itself, starting with `ESC`. It may be incomplete if the
+    /// text ended before it.
+    pub sequence: String,
+    /// The kind of sequence.
+    pub kind: SequenceKind,
+}
+
+/// The kind of a [`Sequence`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SequenceKind {
+    /// A Control Sequence Introducer (`ESC [`) sequence other than SGR, e.g.
+    /// moving the cursor or clearing the screen.
+    Csi,
+    /// An Operating System Command (`ESC ]`) other than a hyperlink, e.g.
+    /// setting the title of the window.
+    Osc,
+    /// A Device Control String (`ESC P`), or one of the other strings ended
+    /// by `ESC \`.
+    Dcs,
+    /// Any other escape sequence, e.g. `ESC 7` saving the cursor position.
+    Escape,
+}
+
+/// Parses text containing SGR sequences into segments of text with their
+/// colors and style.
+///
+/// The 16, 256 and truecolor colors are understood, in their `;` and `:`
+/// separated forms, as well as underline styles and colors and OSC 8
+/// hyperlinks. Other escape sequences are skipped and reported in
+/// [`Parsed::skipped`].
+///
+/// ```rust
+/// use colored::{ansi, Color, Colorize};
+/// let parsed = ansi::parse("\x1b[2K\x1b[38;5;208mwarning\x1b[39m: done");
+/// assert_eq!(parsed.segments, ["warning".color(Color::AnsiColor(208)), ": done".into()]);
+/// assert_eq!(parsed.skipped[0].sequence, "\x1b[2K");
+/// ```
+#[must_use]
+pub fn parse(input: &str) -> Parsed {
+    let mut parser = Parser::default();
+    let mut start = 0;
+    while let Some(offset) = input[start..].find('\x1B').map(|offset| start + offset) {
+        parser.push_text(&input[start..offset]);
+        let (len, kind) = sequence_len(&input.as_bytes()[offset..]);
+        let sequence = &input[offset..offset + len];
+        if !parser.apply(sequence, kind) {
+            parser.parsed.skipped.push(Sequence {
+                offset,
+                sequence: sequence.to_owned(),
+                kind,
+            });
+        }
+        start = offset + len;
+    }
+    parser.push_text(&input[start..]);
+    parser.parsed
+}
+
+/// Removes the escape sequences from a text, e.g. a rendered
+/// [`ColoredString`]. The text is borrowed if it has none.
+///
+/// ```rust
+/// use colored::{strip_ansi, Colorize};
+/// colored::control::set_override(true);
+/// assert_eq!(strip_ansi(&"error".red().bold().to_string()), "error");
+/// # colored::control::unset_override();
+/// ```
+#[must_use]
+pub fn strip_ansi(input: &str) -> Cow<'_, str> {
+    let Some(first) = input.find('\x1B') else {
+        return Cow::Borrowed(input);
+    };
+    let mut stripped = String::with_capacity(input.len());
+    let mut start = 0;
+    let mut next = Some(first);
+    while let Some(offset) = next {
+        stripped.push_str(&input[start..offset]);
+        start = offset + sequence_len(&input.as_bytes()[offset..]).0;
+        next = input[start..].find('\x1B').map(|offset| start + offset);
+    }
+    stripped.push_str(&input[start..]);
+    Cow::Owned(stripped)
+}
+
+/// Gets the number of columns a text takes in a terminal, ignoring its
+/// escape sequences.
+///
+/// Each grapheme cluster takes one column, or two for the wide East Asian
+/// characters and emojis, including the emoji sequences joined with zero
+/// width joiners. Control characters take no column.
+///
+/// ```rust
+/// use colored::{display_width, Colorize};
+/// colored::control::set_override(true);
+/// assert_eq!(display_width(&"日本".red().to_string()), 4);
+/// assert_eq!(display_width("👩‍🔬 ok"), 5);
+/// # colored::control::unset_override();
+/// ```
+#[must_use]
+pub fn display_width(input: &str) -> usize {
+    strip_ansi(input)
+        .graphemes(true)
+        .filter(|grapheme| !grapheme.starts_with(char::is_control))
+        .map(|grapheme| grapheme.width().min(2))
+        .sum()
+}
+
+/// Finds the escape sequences of a text, as byte ranges.
+pub(crate) fn sequences(input: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        let offset = start + input[start..].find('\x1B')?;
+        start = offset + sequence_len(&input.as_bytes()[offset..]).0;
+        Some(offset..start)
+    })
+}
+
+/// Gets the parameters of an SGR sequence, e.g. `1;31` for `ESC[1;31m`.
+pub(crate) fn sgr_params(sequence: &str) -> Option<&str> {
+    sequence
+        .strip_prefix("\x1B[")?
+        .strip_suffix('m')
+        .filter(|params| {
+            params
+                .bytes()
+                .all(|b| b.is_ascii_digit() || b == b';' || b == b':')
+        })
+}
+
+/// Gets the length and kind of the escape sequence at the start of `bytes`.
+///
+/// Only ASCII bytes are part of the sequence, except in strings such as
+/// OSCs, so that the sequence always ends on a char boundary.
+fn sequence_len(bytes: &[u8]) -> (usize, SequenceKind) {
+    let string_end = |start: usize, bel: bool| {
+        (start..bytes.len())
+            .find_map(|i| match bytes[i..] {
+                [0x07, ..] if bel => Some(i + 1),
+                [0x1B, b'\\', ..] => Some(i + 2),
+                _ => None,
+            })
+            .unwrap_or(bytes.len())
+    };
+    let skip = |start: usize, range: std::ops::RangeInclusive<u8>| {
+        start
+            + bytes[start..]
+                .iter()
+                .take_while(|b| range.contains(b))
+                .count()
+    };
+
+    match bytes.get(1) {
+        Some(b'[') => {
+            let end = skip(skip(2, 0x30..=0x3F), 0x20..=0x2F);
+            let end = match bytes.get(end) {
+                Some(0x40..=0x7E) => end + 1,
+                _ => end,
+            };
+            (end, SequenceKind::Csi)
+        }
+        Some(b']') => (string_end(2, true), SequenceKind::Osc),
+        Some(b'P' | b'X' | b'^' | b'_') => (string_end(2, false), SequenceKind::Dcs),
+        _ => {
+            let end = skip(1, 0x20..=0x2F);
+            let end = match bytes.get(end) {
+                Some(0x30..=0x7E) => end + 1,
+                _ => end,
+            };
+            (end, SequenceKind::Escape)
+        }
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    parsed: Parsed,
+    /// The colors and style set by the sequences so far, with an empty text.
+    current: ColoredString,
+}
+
+impl Parser {
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let current = &self.current;
+        match self.parsed.segments.last_mut() {
+            Some(last)
+                if last.fgcolor == current.fgcolor
+                    && last.bgcolor == current.bgcolor
+                    && last.style == current.style
+                    && last.underline_color == current.underline_color
+                    && last.underline_style == current.underline_style
+                    && last.hyperlink == current.hyperlink =>
+            {
+                last.input.push_str(text);
+            }
+            _ => self.parsed.segments.push(ColoredString {
+                input: text.to_owned(),
+                ..current.clone()
+            }),
+        }
+    }
+
+    /// Applies an SGR or hyperlink sequence, returning false for the other
+    /// ones.
+    fn apply(&mut self, sequence: &str, kind: SequenceKind) -> bool {
+        match kind {
+            SequenceKind::Csi => sgr_params(sequence)
+                .map(|params| self.apply_sgr(params))
+                .is_some(),
+            SequenceKind::Osc => {
+                // an unterminated sequence at the end of the text has no
+                // terminator to strip
+                let osc = &sequence[2..];
+                let osc = osc
+                    .strip_suffix('\x07')
+                    .or_else(|| osc.strip_suffix("\x1B\\"))
+                    .unwrap_or(osc);
+                match osc.strip_prefix("8;").and_then(|link| link.split_once(';')) {
+                    Some((_, url)) => {
+                        self.current.hyperlink = Some(url.to_owned()).filter(|url| !url.is_empty());
+                        true
+                    }
+                    None => false,
+                }
+            }
+            SequenceKind::Dcs | SequenceKind::Escape => false,
+        }
+    }
+
+    fn apply_sgr(&mut self, params: &str) {
+        let current = &mut self.current;
+        let mut params = params.split(';');
+        while let Some(param) = params.next() {
+            let mut subparams = param.split(':');
+            let code = subparams.next().unwrap_or_default();
+            let Ok(code) = (if code.is_empty() {
+                Ok(0)
+            } else {
+                code.parse::<u8>()
+            }) else {
+                continue;
+            };
+            match code {
+                0 => {
+                    *current = ColoredString {
+                        hyperlink: current.hyperlink.take(),
+                        ..ColoredString::default()
+                    }
+                }
+                1 => current.style.add(Styles::Bold),
+                2 => current.style.add(Styles::Dimmed),
+                3 => current.style.add(Styles::Italic),
+                4 => {
+                    let underline_style = match subparams.next() {
+                        None | Some("1") => Some(UnderlineStyle::Single),
+                        Some("2") => Some(UnderlineStyle::Double),
+                        Some("3") => Some(UnderlineStyle::Curly),
+                        Some("4") => Some(UnderlineStyle::Dotted),
+                        Some("5") => Some(UnderlineStyle::Dashed),
+                        Some(_) => None,
+                    };
+                    if let Some(underline_style) = underline_style {
+                        current.style.add(Styles::Underline);
+                        current.underline_style = underline_style;
+                    } else {
+                        current.style.remove(Styles::Underline);
+                        current.underline_style = UnderlineStyle::Single;
+                    }
+                }
+                5 => current.style.add(Styles::Blink),
+                6 => current.style.add(Styles::RapidBlink),
+                7 => current.style.add(Styles::Reversed),
+                8 => current.style.add(Styles::Hidden),
+                9 => current.style.add(Styles::Strikethrough),
+                21 => current.style.add(Styles::DoubleUnderline),
+                22 => {
+                    current.style.remove(Styles::Bold);
+                    current.style.remove(Styles::Dimmed);
+                }
+                23 => current.style.remove(Styles::Italic),
+                24 => {
+                    current.style.remove(Styles::Underline);
+                    current.style.remove(Styles::DoubleUnderline);
+                    current.underline_style = UnderlineStyle::Single;
+                }
+                25 => {
+                    current.style.remove(Styles::Blink);
+                    current.style.remove(Styles::RapidBlink);
+                }
+                27 => current.style.remove(Styles::Reversed),
+                28 => current.style.remove(Styles::Hidden),
+                29 => current.style.remove(Styles::Strikethrough),
+                30..=37 => current.fgcolor = Some(Color::from_basic_index(code - 30)),
+                39 => current.fgcolor = None,
+                40..=47 => current.bgcolor = Some(Color::from_basic_index(code - 40)),
+                49 => current.bgcolor = None,
+                51 => current.style.add(Styles::Framed),
+                52 => current.style.add(Styles::Encircled),
+                53 => current.style.add(Styles::Overline),
+                54 => {
+                    current.style.remove(Styles::Framed);
+                    current.style.remove(Styles::Encircled);
+                }
+                55 => current.style.remove(Styles::Overline),
+                59 => current.underline_color = None,
+                73 => current.style.add(Styles::Superscript),
+                74 => current.style.add(Styles::Subscript),
+                75 => {
+                    current.style.remove(Styles::Superscript);
+                    current.style.remove(Styles::Subscript);
+                }
+                90..=97 => current.fgcolor = Some(Color::from_basic_index(code - 90 + 8)),
+                100..=107 => current.bgcolor = Some(Color::from_basic_index(code - 100 + 8)),
+                38 | 48 | 58 => {
+                    let color = if param.contains(':') {
+                        extended_color(&mut subparams, true)
+                    } else {
+                        extended_color(&mut params, false)
+                    };
+                    match code {
+                        38 => current.fgcolor = color.or(current.fgcolor),
+                        48 => current.bgcolor = color.or(current.bgcolor),
+                        _ => current.underline_color = color.or(current.underline_color),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parses the parameters of a 256 color (`5;n`) or truecolor (`2;r;g;b`)
+/// color. The `:` separated form may have a color space before the channels
+/// (`2::r:g:b`).
+fn extended_color<'a>(args: &mut impl Iterator<Item = &'a str>, colons: bool) -> Option<Color> {
+    match args.next()? {
+        "5" => args.next()?.parse().ok().map(Color::AnsiColor),
+        "2" => {
+            let channels: Vec<&str> = if colons {
+                let args: Vec<&str> = args.collect();
+                match args.len() {
+                    3 => args,
+                    _ => args.into_iter().skip(1).take(3).collect(),
+                }
+            } else {
+                args.take(3).collect()
+            };
+            match channels[..] {
+                [r, g, b] => Some(Color::TrueColor {
+                    r: r.parse().ok()?,
+                    g: g.parse().ok()?,
+                    b: b.parse().ok()?,
+                }),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Colorize;
+
+    #[test]
+    fn strip() {
+        assert!(matches!(strip_ansi("plain"), Cow::Borrowed("plain")));
+        assert_eq!(
+            strip_ansi("\x1b[1;31mé\x1b]8;;https://example.com\x07rr\x1b]8;;\x07\x1b[0mor\x1b[2"),
+            "érror"
+        );
+    }
+
+    #[test]
+    fn width() {
+        let cases = [
+            ("", 0),
+            ("abc", 3),
+            ("\x1b[31mabc\x1b[0m\x1b]0;title\x07", 3),
+            ("e\u{301}", 1),
+            ("日本語", 6),
+            ("ｆｕｌｌ", 8),
+            ("🦀", 2),
+            ("❤\u{fe0f}", 2),
+            ("👨\u{200d}👩\u{200d}👧", 2),
+            ("🇫🇷", 2),
+            ("a\tb\r\n", 2),
+        ];
+        for (text, width) in cases {
+            assert_eq!(display_width(text), width, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(parse("").segments, []);
+        assert_eq!(parse("héllo wörld").segments, ["héllo wörld".into()]);
+    }
+
+    #[test]
+    fn colors() {
+        let parsed = parse("\x1b[31;44ma\x1b[93;100mb\x1b[38;5;208;48;2;1;2;3mc\x1b[0m");
+        assert_eq!(
+            parsed.segments,
+            [
+                "a".red().on_blue(),
+                "b".bright_yellow().on_bright_black(),
+                "c".color(Color::AnsiColor(208))
+                    .on_color(Color::TrueColor { r: 1, g: 2, b: 3 }),
+            ]
+        );
+        assert_eq!(parsed.skipped, []);
+    }
+
+    #[test]
+    fn colon_separated_colors() {
+        let truecolor = Color::TrueColor { r: 1, g: 2, b: 3 };
+        for sequence in [
+            "\x1b[38:2:1:2:3m",
+            "\x1b[38:2::1:2:3m",
+            "\x1b[38:2:0:1:2:3m",
+        ] {
+            let text = format!("{sequence}x");
+            assert_eq!(
+                parse(&text).segments,
+                ["x".color(truecolor)],
+                "{sequence:?}"
+            );
+        }
+        assert_eq!(
+            parse("\x1b[48:5:17;1mx").segments,
+            ["x".on_color(Color::AnsiColor(17)).bold()]
+        );
+        // an invalid color keeps the previous one
+        assert_eq!(parse("\x1b[31m\x1b[38:2:1mx").segments, ["x".red()]);
+    }
+
+    #[test]
+    fn partial_resets() {
+        let parsed = parse("\x1b[1;2;3;32;41ma\x1b[22mb\x1b[39mc\x1b[49;23md\x1b[me");
+        assert_eq!(
+            parsed.segments,
+            [
+                "a".bold().dimmed().italic().green().on_red(),
+                "b".italic().green().on_red(),
+                "c".italic().on_red(),
+                "de".into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn underlines() {
+        let parsed = parse("\x1b[4:3;58;5;1ma\x1b[59mb\x1b[4:0mc");
+        assert_eq!(
+            parsed.segments,
+            [
+                "a".underline_style(UnderlineStyle::Curly)
+                    .underline_color(Color::AnsiColor(1)),
+                "b".underline_style(UnderlineStyle::Curly),
+                "c".into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn segments_are_merged() {
+        let parsed = parse("\x1b[31ma\x1b[1m\x1b[22mb\x1b[0m\x1b[0mc");
+        assert_eq!(parsed.segments, ["ab".red(), "c".into()]);
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn round_trip() {
+        let text = crate::control::with_override(true, || {
+            format!("{} {}", "error".red().bold(), "details".on_blue())
+        });
+        assert_eq!(
+            parse(&text).segments,
+            ["error".red().bold(), " ".into(), "details".on_blue()]
+        );
+    }
+
+    #[test]
+    fn hyperlinks() {
+        let parsed = parse("\x1b]8;;https://example.com\x1b\\\x1b[1mlink\x1b[0m!\x1b]8;;\x07 end");
+        assert_eq!(
+            parsed.segments,
+            [
+                "link".bold().hyperlink("https://example.com"),
+                "!".hyperlink("https://example.com"),
+                " end".into(),
+            ]
+        );
+        assert_eq!(parsed.skipped, []);
+
+        // only the terminator is stripped, not the trailing `\` of the url
+        let parsed = parse("\x1b]8;;file:///C:\\dir\\\x07dir\x1b]8;;\x1b\\");
+        assert_eq!(parsed.segments, ["dir".hyperlink("file:///C:\\dir\\")]);
+        let parsed = parse("\x1b]8;;file:///C:\\dir\\\x1b\\dir");
+        assert_eq!(parsed.segments, ["dir".hyperlink("file:///C:\\dir\\")]);
+    }
+
+    #[test]
+    fn other_sequences_are_skipped() {
+        let parsed = parse("a\x1b[2Kb\x1b]0;title\x07c\x1b7d\x1bPdata\x1b\\e\x1b[?25lf");
+        assert_eq!(parsed.segments, ["abcdef".into()]);
+        let skipped: Vec<_> = parsed
+            .skipped
+            .iter()
+            .map(|s| (s.offset, s.sequence.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            skipped,
+            [
+                (1, "\x1b[2K", SequenceKind::Csi),
+                (6, "\x1b]0;title\x07", SequenceKind::Osc),
+                (17, "\x1b7", SequenceKind::Escape),
+                (20, "\x1bPdata\x1b\\", SequenceKind::Dcs),
+                (29, "\x1b[?25l", SequenceKind::Csi),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_sequences() {
+        let parsed = parse("a\x1b[31");
+        assert_eq!(parsed.segments, ["a".into()]);
+        assert_eq!(parsed.skipped[0].sequence, "\x1b[31");
+        let parsed = parse("a\x1b]0;tïtle");
+        assert_eq!(parsed.skipped[0].kind, SequenceKind::Osc);
+        let parsed = parse("a\x1bé");
+        assert_eq!(parsed.segments, ["aé".into()]);
+        assert_eq!(parsed.skipped[0].sequence, "\x1b");
+    }
+}
@@ -1,11 +1,22 @@
-use std::{borrow::Cow, cmp, env, str::FromStr};
+use crate::{
+    colorspace::{srgb_to_linear, Lab, OkLab, OkLch},
+    control, css,
+    render::{ColorCode, Layer},
+    ColorParseError, ColorParseErrorKind, CustomColor, Palette,
+};
+use std::{borrow::Cow, fmt, str::FromStr};
 use Color::{
-    AnsiColor, Black, Blue, BrightBlack, BrightBlue, BrightCyan, BrightGreen, BrightMagenta,
-    BrightRed, BrightWhite, BrightYellow, Cyan, Green, Magenta, Red, TrueColor, White, Yellow,
+    Adaptive, AnsiColor, Black, Blue, BrightBlack, BrightBlue, BrightCyan, BrightGreen,
+    BrightMagenta, BrightRed, BrightWhite, BrightYellow, Cyan, Green, Magenta, Red, TrueColor,
+    White, Yellow,
 };
 /// The 8 standard colors.
+///
+/// The enum is `#[non_exhaustive]`, so that new kinds of colors can be added
+/// without breaking the `match`es on it.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[allow(missing_docs)]
+#[non_exhaustive]
 pub enum Color {
     Black,
     Red,
@@ -24,240 +35,936 @@ pub enum Color {
     BrightCyan,
     BrightWhite,
     AnsiColor(u8),
-    TrueColor { r: u8, g: u8, b: u8 },
+    TrueColor {
+        r: u8,
+        g: u8,
+        b: u8,
+    },
+    /// A color whose variant is picked from the [`Theme`] of the terminal
+    /// when rendered.
+    Adaptive(AdaptiveColor),
+    /// The default foreground or background color of the terminal (SGR 39
+    /// and 49). Unlike no color at all, it resets a color set by an
+    /// enclosing `ColoredString`.
+    ///
+    /// As the actual color is unknown, it is converted to RGB as [`White`],
+    /// and the color transformations return `TrueColor`s like for any other
+    /// color, except [`Color::simulate`] and [`Color::daltonize`] which keep
+    /// it unchanged.
+    ///
+    /// [`White`]: Color::White
+    Default,
+}
+
+/// The colors a terminal can display. The colors it doesn't support are
+/// downgraded to the closest one it does.
+///
+/// It is detected from the environment, and can be set with
+/// [`control::set_color_support`](crate::control::set_color_support).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// The 16 standard colors only.
+    Basic,
+    /// The 256 color palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Detects the colors supported by the terminal from the `COLORTERM` and
+    /// `TERM` environment variables.
+    ///
+    /// Truecolors need `COLORTERM=truecolor` or `COLORTERM=24bit`. The 256
+    /// colors are assumed unless `TERM` names a terminal known to lack them,
+    /// since nearly all terminals display them, whatever their `TERM`, as
+    /// does the Windows console, where it is unset.
+    pub(crate) fn detect(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        match (colorterm, term) {
+            (Some("truecolor" | "24bit"), _) => Self::TrueColor,
+            (_, Some(term)) if ["dumb", "linux", "ansi", "cons25"].contains(&term) => Self::Basic,
+            (_, Some(term)) if term.starts_with("vt") => Self::Basic,
+            _ => Self::Ansi256,
+        }
+    }
 }
 
 fn truecolor_support() -> bool {
-    let truecolor = env::var("COLORTERM");
-    truecolor.is_ok_and(|truecolor| truecolor == "truecolor" || truecolor == "24bit")
+    control::color_support() == ColorSupport::TrueColor
+}
+
+fn ansi256_support() -> bool {
+    control::color_support() >= ColorSupport::Ansi256
+}
+
+/// The 16 standard colors, in the order of their index in the 256 color
+/// palette.
+const BASIC_COLORS: [Color; 16] = [
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+];
+
+/// The metric used to find the closest color when a color has to be
+/// downgraded to what the terminal supports.
+///
+/// The metric used by default is [`DistanceMetric::OkLab`]. It can be changed
+/// with [`control::set_distance_metric`](crate::control::set_distance_metric).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DistanceMetric {
+    /// Squared euclidean distance between the RGB values. The fastest, but
+    /// the least accurate as RGB is not perceptually uniform.
+    Euclidean,
+    /// Euclidean distance in the `OKLab` color space. A good trade-off
+    /// between accuracy and speed.
+    #[default]
+    OkLab,
+    /// The CIEDE2000 color difference. The most accurate, but also the
+    /// slowest.
+    Ciede2000,
+}
+
+impl DistanceMetric {
+    /// Computes the distance between two RGB values. The result can only be
+    /// compared to other distances computed with the same metric.
+    fn distance(self, rgb1: (u8, u8, u8), rgb2: (u8, u8, u8)) -> f32 {
+        match self {
+            Self::Euclidean => {
+                let ((r1, g1, b1), (r2, g2, b2)) = (rgb1, rgb2);
+                let rd = f32::from(r1.abs_diff(r2));
+                let gd = f32::from(g1.abs_diff(g2));
+                let bd = f32::from(b1.abs_diff(b2));
+                bd.mul_add(bd, rd.mul_add(rd, gd * gd))
+            }
+            Self::OkLab => OkLab::from_rgb(rgb1).distance_squared(OkLab::from_rgb(rgb2)),
+            Self::Ciede2000 => Lab::from_rgb(rgb1).ciede2000(Lab::from_rgb(rgb2)),
+        }
+    }
 }
 
 #[allow(missing_docs)]
 impl Color {
     #[must_use]
     pub fn to_fg_str(&self) -> Cow<'static, str> {
-        match *self {
-            Self::Black => "30".into(),
-            Self::Red => "31".into(),
-            Self::Green => "32".into(),
-            Self::Yellow => "33".into(),
-            Self::Blue => "34".into(),
-            Self::Magenta => "35".into(),
-            Self::Cyan => "36".into(),
-            Self::White => "37".into(),
-            Self::BrightBlack => "90".into(),
-            Self::BrightRed => "91".into(),
-            Self::BrightGreen => "92".into(),
-            Self::BrightYellow => "93".into(),
-            Self::BrightBlue => "94".into(),
-            Self::BrightMagenta => "95".into(),
-            Self::BrightCyan => "96".into(),
-            Self::BrightWhite => "97".into(),
-            Self::TrueColor { .. } if !truecolor_support() => {
-                self.closest_color_euclidean().to_fg_str()
-            }
-            Self::AnsiColor(code) => format!("38;5;{code}").into(),
-            Self::TrueColor { r, g, b } => format!("38;2;{r};{g};{b}").into(),
-        }
+        self.code(Layer::Foreground).to_string().into()
+    }
+
+    /// The parameters of the SGR sequence setting the color of underlines
+    /// (SGR 58), see [`UnderlineStyle`](crate::UnderlineStyle).
+    #[must_use]
+    pub fn to_underline_str(&self) -> Cow<'static, str> {
+        self.code(Layer::Underline).to_string().into()
     }
 
     #[must_use]
     pub fn to_bg_str(&self) -> Cow<'static, str> {
-        match *self {
-            Self::Black => "40".into(),
-            Self::Red => "41".into(),
-            Self::Green => "42".into(),
-            Self::Yellow => "43".into(),
-            Self::Blue => "44".into(),
-            Self::Magenta => "45".into(),
-            Self::Cyan => "46".into(),
-            Self::White => "47".into(),
-            Self::BrightBlack => "100".into(),
-            Self::BrightRed => "101".into(),
-            Self::BrightGreen => "102".into(),
-            Self::BrightYellow => "103".into(),
-            Self::BrightBlue => "104".into(),
-            Self::BrightMagenta => "105".into(),
-            Self::BrightCyan => "106".into(),
-            Self::BrightWhite => "107".into(),
-            Self::AnsiColor(code) => format!("48;5;{code}").into(),
-            Self::TrueColor { .. } if !truecolor_support() => {
-                self.closest_color_euclidean().to_bg_str()
+        self.code(Layer::Background).to_string().into()
+    }
+
+    /// The SGR parameters setting the color on the `layer`, once downgraded
+    /// to what the terminal supports. They are written without allocating.
+    pub(crate) fn code(self, layer: Layer) -> ColorCode {
+        match self {
+            TrueColor { .. } if !truecolor_support() => self.downgrade().code(layer),
+            AnsiColor(_) if !ansi256_support() => self.downgrade().code(layer),
+            AnsiColor(index) => ColorCode::Ansi(layer, index),
+            TrueColor { r, g, b } => ColorCode::Rgb(layer, r, g, b),
+            Adaptive(adaptive) => adaptive.resolve().code(layer),
+            Self::Default => ColorCode::Basic(layer.extended() + 1),
+            color => {
+                let index = color.ansi_index().unwrap_or_default();
+                let base = match layer {
+                    Layer::Foreground => 30,
+                    Layer::Background => 40,
+                    // the underline colors have no basic codes
+                    Layer::Underline => return ColorCode::Ansi(layer, index),
+                };
+                if index < 8 {
+                    ColorCode::Basic(base + index)
+                } else {
+                    ColorCode::Basic(base + 60 + index - 8)
+                }
             }
-            Self::TrueColor { r, g, b } => format!("48;2;{r};{g};{b}").into(),
         }
     }
 
-    /// Gets the closest plain color to the `TrueColor`
-    fn closest_color_euclidean(self) -> Self {
+    /// Downgrades a `TrueColor` or an `AnsiColor` to the richest color the
+    /// terminal supports: an index of the 256 color palette if available, or
+    /// else one of the 16 plain colors.
+    fn downgrade(self) -> Self {
+        let metric = control::distance_metric();
+        control::with_current_palette(|palette| {
+            if ansi256_support() {
+                self.closest_ansi256(metric, palette)
+            } else {
+                self.closest_color(metric, palette)
+            }
+        })
+    }
+
+    /// Gets the closest `AnsiColor` of the 6x6x6 color cube or of the
+    /// grayscale ramp to the `TrueColor`.
+    ///
+    /// The 16 system colors are left out since their actual values depend on
+    /// the terminal's theme, which the palette may not match.
+    fn closest_ansi256(self, metric: DistanceMetric, palette: &Palette) -> Self {
         match self {
-            TrueColor {
-                r: r1,
-                g: g1,
-                b: b1,
-            } => {
-                let colors = vec![
-                    Black,
-                    Red,
-                    Green,
-                    Yellow,
-                    Blue,
-                    Magenta,
-                    Cyan,
-                    White,
-                    BrightBlack,
-                    BrightRed,
-                    BrightGreen,
-                    BrightYellow,
-                    BrightBlue,
-                    BrightMagenta,
-                    BrightCyan,
-                    BrightWhite,
-                ]
-                .into_iter()
-                .map(|c| (c, c.into_truecolor()));
-                let distances = colors.map(|(c_original, c)| {
-                    if let TrueColor { r, g, b } = c {
-                        let rd = cmp::max(r, r1) - cmp::min(r, r1);
-                        let gd = cmp::max(g, g1) - cmp::min(g, g1);
-                        let bd = cmp::max(b, b1) - cmp::min(b, b1);
-                        let rd: u32 = rd.into();
-                        let gd: u32 = gd.into();
-                        let bd: u32 = bd.into();
-                        let distance = rd.pow(2) + gd.pow(2) + bd.pow(2);
-                        (c_original, distance)
-                    } else {
-                        unimplemented!("{:?} not a TrueColor", c)
-                    }
-                });
-                distances.min_by(|(_, d1), (_, d2)| d1.cmp(d2)).unwrap().0
+            TrueColor { r, g, b } => {
+                let index = (16..=255)
+                    .map(|index| {
+                        let rgb = palette.get(index);
+                        (index, metric.distance((rgb.r, rgb.g, rgb.b), (r, g, b)))
+                    })
+                    .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+                    .map_or(16, |(index, _)| index);
+                AnsiColor(index)
             }
             c => c,
         }
     }
 
-    fn into_truecolor(self) -> Self {
+    /// Gets the closest plain color of the palette to the `TrueColor` or
+    /// the `AnsiColor`.
+    fn closest_color(self, metric: DistanceMetric, palette: &Palette) -> Self {
         match self {
-            Black => TrueColor { r: 0, g: 0, b: 0 },
-            Red => TrueColor { r: 205, g: 0, b: 0 },
-            Green => TrueColor { r: 0, g: 205, b: 0 },
-            Yellow => TrueColor {
-                r: 205,
-                g: 205,
-                b: 0,
-            },
-            Blue => TrueColor { r: 0, g: 0, b: 238 },
-            Magenta => TrueColor {
-                r: 205,
-                g: 0,
-                b: 205,
-            },
-            Cyan => TrueColor {
-                r: 0,
-                g: 205,
-                b: 205,
-            },
-            White => TrueColor {
-                r: 229,
-                g: 229,
-                b: 229,
-            },
-            BrightBlack => TrueColor {
-                r: 127,
-                g: 127,
-                b: 127,
+            AnsiColor(index) if index < 16 => BASIC_COLORS[usize::from(index)],
+            AnsiColor(_) => {
+                let (r, g, b) = self.rgb_in(palette);
+                TrueColor { r, g, b }.closest_color(metric, palette)
+            }
+            TrueColor { r, g, b } => BASIC_COLORS
+                .into_iter()
+                .zip(palette.basic())
+                .map(|(c, rgb)| (c, metric.distance((rgb.r, rgb.g, rgb.b), (r, g, b))))
+                .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+                .map_or(Black, |(c, _)| c),
+            c => c,
+        }
+    }
+
+    /// Creates a `TrueColor` from a hex color, in a `const` if needed. See
+    /// [`CustomColor::from_hex`] and the [`hex!`](crate::hex) macro.
+    ///
+    /// # Panics
+    /// Panics if `hex` isn't a valid hex color.
+    #[must_use]
+    pub const fn from_hex(hex: &str) -> Self {
+        let CustomColor { r, g, b } = CustomColor::from_hex(hex);
+        TrueColor { r, g, b }
+    }
+
+    /// Gets the RGB value of the color.
+    ///
+    /// The standard colors and `AnsiColor`s are looked up in the current
+    /// [`Palette`], which is the xterm one by default: the 16 system colors,
+    /// then the 6x6x6 color cube and the 24 steps grayscale ramp.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// assert_eq!(Color::Blue.to_rgb(), CustomColor::new(0, 0, 238));
+    /// assert_eq!(Color::AnsiColor(208).to_rgb(), CustomColor::new(255, 135, 0));
+    /// assert_eq!(Color::AnsiColor(244).to_rgb(), CustomColor::new(128, 128, 128));
+    /// ```
+    #[must_use]
+    pub fn to_rgb(self) -> CustomColor {
+        self.rgb().into()
+    }
+
+    /// Converts the color to a `TrueColor`, see [`Color::to_rgb`].
+    #[must_use]
+    pub fn into_truecolor(self) -> Self {
+        let (r, g, b) = self.rgb();
+        TrueColor { r, g, b }
+    }
+
+    /// Gets the RGB value of the color, looking the standard colors and
+    /// `AnsiColor`s up in the current [`Palette`].
+    fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            TrueColor { r, g, b } => (r, g, b),
+            Adaptive(adaptive) => adaptive.resolve().rgb(),
+            c => control::with_current_palette(|palette| c.rgb_in(palette)),
+        }
+    }
+
+    /// Gets the RGB value of the color, looking the standard colors and
+    /// `AnsiColor`s up in the given [`Palette`].
+    fn rgb_in(self, palette: &Palette) -> (u8, u8, u8) {
+        match self {
+            TrueColor { r, g, b } => (r, g, b),
+            Adaptive(adaptive) => adaptive.resolve().rgb_in(palette),
+            Self::Default => White.rgb_in(palette),
+            c => {
+                let rgb = palette.get(c.ansi_index().unwrap_or_default());
+                (rgb.r, rgb.g, rgb.b)
+            }
+        }
+    }
+
+    /// Gets the standard color of an index of the 256 color palette, which
+    /// must be lower than 16.
+    pub(crate) const fn from_basic_index(index: u8) -> Self {
+        BASIC_COLORS[index as usize]
+    }
+
+    /// Gets the index of the color in the 256 color palette, for the
+    /// standard colors and `AnsiColor`s.
+    const fn ansi_index(self) -> Option<u8> {
+        match self {
+            Black => Some(0),
+            Red => Some(1),
+            Green => Some(2),
+            Yellow => Some(3),
+            Blue => Some(4),
+            Magenta => Some(5),
+            Cyan => Some(6),
+            White => Some(7),
+            BrightBlack => Some(8),
+            BrightRed => Some(9),
+            BrightGreen => Some(10),
+            BrightYellow => Some(11),
+            BrightBlue => Some(12),
+            BrightMagenta => Some(13),
+            BrightCyan => Some(14),
+            BrightWhite => Some(15),
+            AnsiColor(index) => Some(index),
+            TrueColor { .. } | Adaptive(_) | Self::Default => None,
+        }
+    }
+}
+
+/// Whether the terminal has a light or a dark background, which decides the
+/// variant of [`AdaptiveColor`]s.
+///
+/// It is guessed from the `COLORFGBG` environment variable, and can be
+/// detected from the background color of the terminal with
+/// [`control::detect_theme`](crate::control::detect_theme) or set with
+/// [`control::set_theme`](crate::control::set_theme).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    /// A light background, with dark text.
+    Light,
+    /// A dark background, with light text.
+    #[default]
+    Dark,
+}
+
+impl Theme {
+    /// Gets the theme matching a background color: `Dark` if white text is
+    /// more readable than black text on it, `Light` otherwise.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// assert_eq!(Theme::from_background(Color::Blue), Theme::Dark);
+    /// assert_eq!(Theme::from_background(Color::BrightYellow), Theme::Light);
+    /// ```
+    #[must_use]
+    pub fn from_background(background: Color) -> Self {
+        if Color::black_or_white_on(background) == Black {
+            Self::Light
+        } else {
+            Self::Dark
+        }
+    }
+}
+
+/// A color with a variant for light and dark backgrounds, picked from the
+/// current [`Theme`] when the color is rendered.
+///
+/// ```rust
+/// # use colored::*;
+/// // dim text that stays visible on both dark and light backgrounds
+/// let dim = AdaptiveColor::new(Color::BrightBlack, Color::White);
+/// "note".color(dim);
+///
+/// assert_eq!(dim.for_theme(Theme::Light), Color::BrightBlack);
+/// assert_eq!(dim.for_theme(Theme::Dark), Color::White);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveColor {
+    light: PackedColor,
+    dark: PackedColor,
+}
+
+/// A non-adaptive color, packed so that [`AdaptiveColor`] and thus
+/// [`Color`] can stay `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct PackedColor {
+    /// The index of a standard color, or one of the tags below.
+    tag: u8,
+    rgb: [u8; 3],
+}
+
+impl PackedColor {
+    const ANSI: u8 = 16;
+    const TRUE_COLOR: u8 = 17;
+    const DEFAULT: u8 = 18;
+
+    const fn pack(color: Color) -> Self {
+        match color {
+            AnsiColor(index) => Self {
+                tag: Self::ANSI,
+                rgb: [index, 0, 0],
             },
-            BrightRed => TrueColor { r: 255, g: 0, b: 0 },
-            BrightGreen => TrueColor { r: 0, g: 255, b: 0 },
-            BrightYellow => TrueColor {
-                r: 255,
-                g: 255,
-                b: 0,
+            TrueColor { r, g, b } => Self {
+                tag: Self::TRUE_COLOR,
+                rgb: [r, g, b],
             },
-            BrightBlue => TrueColor {
-                r: 92,
-                g: 92,
-                b: 255,
+            Adaptive(adaptive) => adaptive.dark,
+            Color::Default => Self {
+                tag: Self::DEFAULT,
+                rgb: [0; 3],
             },
-            BrightMagenta => TrueColor {
-                r: 255,
-                g: 0,
-                b: 255,
+            basic => match basic.ansi_index() {
+                Some(tag) => Self { tag, rgb: [0; 3] },
+                None => unreachable!(),
             },
-            BrightCyan => TrueColor {
-                r: 0,
-                g: 255,
-                b: 255,
-            },
-            BrightWhite => TrueColor {
-                r: 255,
-                g: 255,
-                b: 255,
-            },
-            AnsiColor(color) => AnsiColor(color),
-            TrueColor { r, g, b } => TrueColor { r, g, b },
+        }
+    }
+
+    const fn unpack(self) -> Color {
+        let [r, g, b] = self.rgb;
+        match self.tag {
+            Self::ANSI => AnsiColor(r),
+            Self::TRUE_COLOR => TrueColor { r, g, b },
+            Self::DEFAULT => Color::Default,
+            tag => BASIC_COLORS[tag as usize],
+        }
+    }
+}
+
+impl AdaptiveColor {
+    /// Creates a color using `light` on light backgrounds and `dark` on dark
+    /// ones. If they are adaptive themselves, their own variant for the same
+    /// theme is used.
+    #[must_use]
+    pub const fn new(light: Color, dark: Color) -> Self {
+        let light = match light {
+            Adaptive(adaptive) => adaptive.light,
+            light => PackedColor::pack(light),
+        };
+        Self {
+            light,
+            dark: PackedColor::pack(dark),
+        }
+    }
+
+    /// The color used on light backgrounds.
+    #[must_use]
+    pub const fn light(self) -> Color {
+        self.light.unpack()
+    }
+
+    /// The color used on dark backgrounds.
+    #[must_use]
+    pub const fn dark(self) -> Color {
+        self.dark.unpack()
+    }
+
+    /// Gets the variant of the color for a theme.
+    #[must_use]
+    pub const fn for_theme(self, theme: Theme) -> Color {
+        match theme {
+            Theme::Light => self.light(),
+            Theme::Dark => self.dark(),
+        }
+    }
+
+    /// Gets the variant of the color for the current theme, see
+    /// [`control::theme`](crate::control::theme).
+    #[must_use]
+    pub fn resolve(self) -> Color {
+        self.for_theme(control::theme())
+    }
+}
+
+impl fmt::Debug for AdaptiveColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdaptiveColor")
+            .field("light", &self.light())
+            .field("dark", &self.dark())
+            .finish()
+    }
+}
+
+impl From<AdaptiveColor> for Color {
+    fn from(adaptive: AdaptiveColor) -> Self {
+        Adaptive(adaptive)
+    }
+}
+
+impl From<CustomColor> for Color {
+    fn from(CustomColor { r, g, b }: CustomColor) -> Self {
+        TrueColor { r, g, b }
+    }
+}
+
+/// Color manipulation.
+///
+/// These methods work in the perceptual `OKLCH` color space, so that e.g.
+/// lightening two colors by the same amount makes them look equally lighter.
+/// They always return a [`Color::TrueColor`]: the standard colors and
+/// `AnsiColor`s are first converted to their RGB equivalent. Colors that end
+/// up outside of the RGB gamut have their saturation reduced until they fit.
+///
+/// ```rust
+/// # use colored::*;
+/// let brand = Color::TrueColor { r: 255, g: 136, b: 0 };
+/// let hover = brand.lighten(0.1);
+/// let dim = brand.darken(0.2).desaturate(0.5);
+/// let accent = brand.complement();
+/// ```
+impl Color {
+    fn map_oklch(self, f: impl FnOnce(OkLch) -> OkLch) -> Self {
+        let (r, g, b) = f(OkLch::from_rgb(self.rgb())).to_rgb();
+        TrueColor { r, g, b }
+    }
+
+    /// Increases the lightness by `amount`, the lightness ranging from 0
+    /// (black) to 1 (white).
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// assert_eq!(Color::Black.lighten(1.0), Color::TrueColor { r: 255, g: 255, b: 255 });
+    /// ```
+    #[must_use]
+    pub fn lighten(self, amount: f32) -> Self {
+        self.map_oklch(|lch| OkLch {
+            l: lch.l + amount,
+            ..lch
+        })
+    }
+
+    /// Decreases the lightness by `amount`, the lightness ranging from 0
+    /// (black) to 1 (white).
+    #[must_use]
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Increases the saturation (chroma) by a factor of `1 + amount`.
+    ///
+    /// Grays have no saturation to increase, so they are left unchanged.
+    #[must_use]
+    pub fn saturate(self, amount: f32) -> Self {
+        self.map_oklch(|lch| OkLch {
+            c: lch.c * (1.0 + amount),
+            ..lch
+        })
+    }
+
+    /// Decreases the saturation (chroma) by a factor of `1 - amount`, so
+    /// that `desaturate(1.0)` turns the color into a gray of the same
+    /// lightness.
+    #[must_use]
+    pub fn desaturate(self, amount: f32) -> Self {
+        self.saturate(-amount.min(1.0))
+    }
+
+    /// Rotates the hue by `degrees`.
+    #[must_use]
+    pub fn rotate_hue(self, degrees: f32) -> Self {
+        self.map_oklch(|lch| OkLch {
+            h: (lch.h + degrees).rem_euclid(360.0),
+            ..lch
+        })
+    }
+
+    /// The complementary color, i.e. the color with the opposite hue.
+    #[must_use]
+    pub fn complement(self) -> Self {
+        self.rotate_hue(180.0)
+    }
+
+    /// Mixes two colors, `t` going from 0 (only `self`) to 1 (only `other`).
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let white = Color::TrueColor { r: 255, g: 255, b: 255 };
+    /// assert_eq!(Color::Red.mix(white, 0.0), Color::Red.mix(Color::Red, 0.5));
+    /// ```
+    #[must_use]
+    pub fn mix(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let (r, g, b) = OkLab::from_rgb(self.rgb())
+            .mix(OkLab::from_rgb(other.rgb()), t)
+            .to_rgb();
+        TrueColor { r, g, b }
+    }
+
+    /// Inverts the color, e.g. black becomes white.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let color = Color::TrueColor { r: 255, g: 136, b: 0 };
+    /// assert_eq!(color.invert(), Color::TrueColor { r: 0, g: 119, b: 255 });
+    /// ```
+    #[must_use]
+    pub fn invert(self) -> Self {
+        let (r, g, b) = self.rgb();
+        TrueColor {
+            r: 255 - r,
+            g: 255 - g,
+            b: 255 - b,
         }
     }
 }
 
+/// Contrast, as defined by the [Web Content Accessibility Guidelines](https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio).
+///
+/// WCAG requires a contrast ratio of at least 4.5 for regular text (level AA)
+/// and of 7 for enhanced contrast (level AAA).
+impl Color {
+    /// The relative luminance of the color, from 0 for black to 1 for white.
+    #[must_use]
+    pub fn relative_luminance(&self) -> f32 {
+        let (r, g, b) = self.rgb();
+        0.0722f32.mul_add(
+            srgb_to_linear(b),
+            0.2126f32.mul_add(srgb_to_linear(r), 0.7152 * srgb_to_linear(g)),
+        )
+    }
+
+    /// The contrast ratio between two colors, from 1 (no contrast) to 21
+    /// (black on white).
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let ratio = Color::Black.contrast_ratio(&Color::BrightWhite);
+    /// assert!((ratio - 21.0).abs() < 1e-3);
+    /// assert!(Color::BrightYellow.contrast_ratio(&Color::BrightWhite) < 4.5);
+    /// ```
+    #[must_use]
+    pub fn contrast_ratio(&self, other: &Self) -> f32 {
+        let (l1, l2) = (self.relative_luminance(), other.relative_luminance());
+        (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+    }
+
+    /// Picks the candidate with the highest contrast ratio on `bg`, or
+    /// `None` if there are no candidates.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let candidates = [Color::Yellow, Color::Blue, Color::Green];
+    /// assert_eq!(Color::readable_on(Color::White, &candidates), Some(Color::Blue));
+    /// ```
+    #[must_use]
+    pub fn readable_on(bg: Self, candidates: &[Self]) -> Option<Self> {
+        candidates
+            .iter()
+            .map(|candidate| (*candidate, candidate.contrast_ratio(&bg)))
+            .reduce(|best, current| if current.1 > best.1 { current } else { best })
+            .map(|(candidate, _)| candidate)
+    }
+
+    /// Picks whichever of black and bright white is the most readable on
+    /// `bg`, see [`Color::readable_on`].
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// assert_eq!(Color::black_or_white_on(Color::BrightYellow), Color::Black);
+    /// assert_eq!(Color::black_or_white_on(Color::Blue), Color::BrightWhite);
+    /// ```
+    #[must_use]
+    pub fn black_or_white_on(bg: Self) -> Self {
+        Self::readable_on(bg, &[Black, BrightWhite]).unwrap_or(Black)
+    }
+
+    /// Adjusts the lightness of the color as little as possible for it to
+    /// have a contrast ratio of at least `min_ratio` on `bg`.
+    ///
+    /// The color is returned as is if it already has enough contrast.
+    /// Otherwise a `TrueColor` is returned, which is black or white if the
+    /// ratio can't be reached by changing the lightness alone.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let fg = Color::BrightYellow.ensure_contrast(Color::BrightWhite, 4.5);
+    /// assert!(fg.contrast_ratio(&Color::BrightWhite) >= 4.5);
+    /// ```
+    #[must_use]
+    pub fn ensure_contrast(self, bg: Self, min_ratio: f32) -> Self {
+        if self.contrast_ratio(&bg) >= min_ratio {
+            return self;
+        }
+
+        let is_readable = |amount: f32| self.lighten(amount).contrast_ratio(&bg) >= min_ratio;
+        // the smallest change of lightness in the given direction that is
+        // readable, if any
+        let smallest_change = |direction: f32| {
+            if !is_readable(direction) {
+                return None;
+            }
+            let (mut low, mut high) = (0.0, 1.0);
+            for _ in 0..16 {
+                let amount = (low + high) / 2.0;
+                if is_readable(direction * amount) {
+                    high = amount;
+                } else {
+                    low = amount;
+                }
+            }
+            Some(high)
+        };
+
+        match (smallest_change(1.0), smallest_change(-1.0)) {
+            (Some(lighter), Some(darker)) if darker < lighter => self.darken(darker),
+            (Some(lighter), _) => self.lighten(lighter),
+            (None, Some(darker)) => self.darken(darker),
+            (None, None) => {
+                let white = TrueColor {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                };
+                let black = TrueColor { r: 0, g: 0, b: 0 };
+                if black.contrast_ratio(&bg) >= white.contrast_ratio(&bg) {
+                    black
+                } else {
+                    white
+                }
+            }
+        }
+    }
+}
+
+/// Parses a color, falling back to [`Color::White`] if the string is not a
+/// valid color. Use [`str::parse`] or [`Colorize::try_color`](crate::Colorize::try_color)
+/// to detect invalid colors instead.
 impl From<&str> for Color {
     fn from(src: &str) -> Self {
         src.parse().unwrap_or(Self::White)
     }
 }
 
+/// Parses a color, falling back to [`Color::White`] if the string is not a
+/// valid color. Use [`str::parse`] or [`Colorize::try_color`](crate::Colorize::try_color)
+/// to detect invalid colors instead.
 impl From<String> for Color {
     fn from(src: String) -> Self {
         src.parse().unwrap_or(Self::White)
     }
 }
 
+/// The names of the 16 standard colors and of the default color, plus
+/// aliases.
+const COLOR_NAMES: [(&str, Color); 18] = [
+    ("black", Black),
+    ("red", Red),
+    ("green", Green),
+    ("yellow", Yellow),
+    ("blue", Blue),
+    ("magenta", Magenta),
+    ("purple", Magenta),
+    ("cyan", Cyan),
+    ("white", White),
+    ("bright black", BrightBlack),
+    ("bright red", BrightRed),
+    ("bright green", BrightGreen),
+    ("bright yellow", BrightYellow),
+    ("bright blue", BrightBlue),
+    ("bright magenta", BrightMagenta),
+    ("bright cyan", BrightCyan),
+    ("bright white", BrightWhite),
+    ("default", Color::Default),
+];
+
+/// Formats the color so that it can be parsed back with [`FromStr`]: the
+/// name of the standard colors, `default`, `ansi(n)` for `AnsiColor`s, `#rrggbb` for
+/// `TrueColor`s and `light-dark(light, dark)` for `Adaptive` colors.
+///
+/// ```rust
+/// # use colored::*;
+/// assert_eq!(Color::BrightRed.to_string(), "bright red");
+/// assert_eq!(Color::AnsiColor(208).to_string(), "ansi(208)");
+/// let orange = Color::TrueColor { r: 255, g: 136, b: 0 };
+/// assert_eq!(orange.to_string(), "#ff8800");
+/// assert_eq!(orange.to_string().parse(), Ok(orange));
+/// ```
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            AnsiColor(index) => write!(f, "ansi({index})"),
+            TrueColor { r, g, b } => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+            Adaptive(adaptive) => {
+                write!(f, "light-dark({}, {})", adaptive.light(), adaptive.dark())
+            }
+            basic => {
+                let name = COLOR_NAMES
+                    .iter()
+                    .find(|&&(_, color)| color == basic)
+                    .map_or("", |&(name, _)| name);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
 impl FromStr for Color {
-    type Err = ();
+    type Err = ColorParseError;
 
     fn from_str(src: &str) -> Result<Self, Self::Err> {
-        let src = src.to_lowercase();
-
-        match src.as_ref() {
-            "black" => Ok(Self::Black),
-            "red" => Ok(Self::Red),
-            "green" => Ok(Self::Green),
-            "yellow" => Ok(Self::Yellow),
-            "blue" => Ok(Self::Blue),
-            "magenta" | "purple" => Ok(Self::Magenta),
-            "cyan" => Ok(Self::Cyan),
-            "white" => Ok(Self::White),
-            "bright black" => Ok(Self::BrightBlack),
-            "bright red" => Ok(Self::BrightRed),
-            "bright green" => Ok(Self::BrightGreen),
-            "bright yellow" => Ok(Self::BrightYellow),
-            "bright blue" => Ok(Self::BrightBlue),
-            "bright magenta" => Ok(Self::BrightMagenta),
-            "bright cyan" => Ok(Self::BrightCyan),
-            "bright white" => Ok(Self::BrightWhite),
-            s if s.starts_with('#') => parse_hex(&s[1..]).ok_or(()),
-            _ => Err(()),
+        let s = src.trim().to_lowercase();
+
+        let result = if let Some(&(_, color)) = COLOR_NAMES.iter().find(|(name, _)| *name == s) {
+            Ok(color)
+        } else if let Some(hex) = s.strip_prefix('#') {
+            parse_hex(hex)
+        } else if let Some(index) = s.strip_prefix("ansi(") {
+            index
+                .strip_suffix(')')
+                .ok_or(ColorParseErrorKind::InvalidSyntax)
+                .and_then(|index| parse_ansi_index(index.trim()))
+        } else if let Some(args) = s.strip_prefix("light-dark(") {
+            parse_light_dark(args)
+        } else if s.contains('(') {
+            css::parse_function(&s).map(|(r, g, b)| Self::TrueColor { r, g, b })
+        } else if s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+            parse_ansi_index(&s)
+        } else {
+            css::named_color(&s)
+                .map(|(r, g, b)| Self::TrueColor { r, g, b })
+                .ok_or(ColorParseErrorKind::UnknownName)
+        };
+
+        result.map_err(|kind| {
+            let suggestion = if kind == ColorParseErrorKind::UnknownName {
+                suggest(&s)
+            } else {
+                None
+            };
+            ColorParseError::new(src, kind, suggestion)
+        })
+    }
+}
+
+/// Parses the arguments of `light-dark(light, dark)`, after the opening
+/// parenthesis, into an [`AdaptiveColor`].
+fn parse_light_dark(args: &str) -> Result<Color, ColorParseErrorKind> {
+    let args = args
+        .strip_suffix(')')
+        .ok_or(ColorParseErrorKind::InvalidSyntax)?;
+    // the colors may be functions with commas of their own
+    let mut depth = 0_usize;
+    let comma = args.find(|c| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => {}
         }
+        c == ',' && depth == 0
+    });
+    let (light, dark) = comma
+        .map(|comma| (&args[..comma], &args[comma + 1..]))
+        .ok_or(ColorParseErrorKind::InvalidSyntax)?;
+    let parse = |color: &str| color.parse::<Color>().map_err(|error| error.kind());
+    Ok(Adaptive(AdaptiveColor::new(parse(light)?, parse(dark)?)))
+}
+
+fn parse_ansi_index(s: &str) -> Result<Color, ColorParseErrorKind> {
+    match s.parse() {
+        Ok(index) => Ok(Color::AnsiColor(index)),
+        Err(_) if s.parse::<i128>().is_ok() => Err(ColorParseErrorKind::OutOfRange),
+        Err(_) => Err(ColorParseErrorKind::InvalidSyntax),
     }
 }
 
-fn parse_hex(s: &str) -> Option<Color> {
+fn parse_hex(s: &str) -> Result<Color, ColorParseErrorKind> {
+    if !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ColorParseErrorKind::InvalidHexDigit);
+    }
+    // the alpha channel of the 4 and 8 digits notations is dropped
+    let s = match s.len() {
+        4 | 8 => &s[..s.len() / 4 * 3],
+        _ => s,
+    };
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).unwrap();
     if s.len() == 6 {
-        let r = u8::from_str_radix(&s[0..2], 16).ok()?;
-        let g = u8::from_str_radix(&s[2..4], 16).ok()?;
-        let b = u8::from_str_radix(&s[4..6], 16).ok()?;
-        Some(Color::TrueColor { r, g, b })
+        let r = channel(&s[0..2]);
+        let g = channel(&s[2..4]);
+        let b = channel(&s[4..6]);
+        Ok(Color::TrueColor { r, g, b })
     } else if s.len() == 3 {
-        let r = u8::from_str_radix(&s[0..1], 16).ok()?;
+        let r = channel(&s[0..1]);
         let r = r | (r << 4);
-        let g = u8::from_str_radix(&s[1..2], 16).ok()?;
+        let g = channel(&s[1..2]);
         let g = g | (g << 4);
-        let b = u8::from_str_radix(&s[2..3], 16).ok()?;
+        let b = channel(&s[2..3]);
         let b = b | (b << 4);
-        Some(Color::TrueColor { r, g, b })
+        Ok(Color::TrueColor { r, g, b })
     } else {
-        None
+        Err(ColorParseErrorKind::InvalidHexLength)
+    }
+}
+
+/// Finds the known color or function name closest to an unknown name, if it
+/// is close enough to be a likely typo.
+fn suggest(s: &str) -> Option<&'static str> {
+    let (name, args) = match s.split_once('(') {
+        Some((name, _)) => (name.trim_end(), true),
+        None => (s, false),
+    };
+    let candidates: Vec<&'static str> = if args {
+        css::FUNCTION_NAMES
+            .iter()
+            .copied()
+            .chain(["ansi"])
+            .collect()
+    } else {
+        COLOR_NAMES
+            .iter()
+            .map(|&(name, _)| name)
+            .chain(css::NAMED_COLORS.iter().map(|&(name, _)| name))
+            .collect()
+    };
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (candidate, edit_distance(name, candidate)))
+        .filter(|&(_, distance)| distance <= max_distance)
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(candidate, _)| candidate)
+}
+
+/// The edit distance between two strings, counting the transposition of two
+/// adjacent characters as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // distances[i][j] is the distance between a[..i] and b[..j]
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
     }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
 }
 
 #[cfg(test)]
@@ -298,6 +1005,7 @@ mod tests {
             brightmagenta: "bright magenta" => Color::BrightMagenta,
             brightcyan: "bright cyan" => Color::BrightCyan,
             brightwhite: "bright white" => Color::BrightWhite,
+            default: "default" => Color::Default,
 
             invalid: "invalid" => Color::White,
             capitalized: "BLUE" => Color::Blue,
@@ -311,7 +1019,29 @@ mod tests {
             hex6_mixed: "#aBcDeF" => Color::TrueColor { r: 171, g: 205, b: 239 },
             hex_too_short: "#aa" => Color::White,
             hex_too_long: "#aaabbbccc" => Color::White,
-            hex_invalid: "#abcxyz" => Color::White
+            hex_invalid: "#abcxyz" => Color::White,
+            hex4_alpha: "#abcd" => Color::TrueColor { r: 170, g: 187, b: 204 },
+            hex8_alpha: "#abcdef80" => Color::TrueColor { r: 171, g: 205, b: 239 },
+            hex8_invalid_alpha: "#abcdefzz" => Color::White,
+            hex_non_ascii: "#aé" => Color::White,
+
+            css_name: "rebeccapurple" => Color::TrueColor { r: 102, g: 51, b: 153 },
+            css_name_mixed_case: "DarkOrange" => Color::TrueColor { r: 255, g: 140, b: 0 },
+            css_name_gray: "gray" => Color::TrueColor { r: 128, g: 128, b: 128 },
+            basic_name_first: "red" => Color::Red,
+            css_rgb: "rgb(255 128 0)" => Color::TrueColor { r: 255, g: 128, b: 0 },
+            css_rgb_upper: "RGB(255, 128, 0)" => Color::TrueColor { r: 255, g: 128, b: 0 },
+            css_hsl: "hsl(30, 100%, 50%)" => Color::TrueColor { r: 255, g: 128, b: 0 },
+            css_invalid: "rgb(255 128)" => Color::White,
+            surrounding_whitespace: "  blue " => Color::Blue,
+
+            ansi_fn: "ansi(208)" => Color::AnsiColor(208),
+            ansi_fn_spaces: "ansi( 42 )" => Color::AnsiColor(42),
+            ansi_fn_out_of_range: "ansi(256)" => Color::White,
+            ansi_bare: "208" => Color::AnsiColor(208),
+            ansi_bare_zero: "0" => Color::AnsiColor(0),
+            ansi_bare_out_of_range: "256" => Color::White,
+            ansi_bare_negative: "-1" => Color::White
         );
     }
 
@@ -349,6 +1079,7 @@ mod tests {
             brightmagenta: "bright magenta" => Color::BrightMagenta,
             brightcyan: "bright cyan" => Color::BrightCyan,
             brightwhite: "bright white" => Color::BrightWhite,
+            default: "default" => Color::Default,
 
             invalid: "invalid" => Color::White,
             capitalized: "BLUE" => Color::Blue,
@@ -377,8 +1108,141 @@ mod tests {
 
         #[test]
         fn error() {
-            let color: Result<Color, ()> = "bloublou".parse();
-            assert_eq!(Err(()), color);
+            let color: Result<Color, ColorParseError> = "bloublou".parse();
+            let error = color.unwrap_err();
+            assert_eq!(error.input(), "bloublou");
+            assert_eq!(error.kind(), ColorParseErrorKind::UnknownName);
+            assert_eq!(error.suggestion(), None);
+        }
+
+        macro_rules! make_error_test {
+            ( $( $name:ident: $src:expr => $kind:ident, $suggestion:expr );* $(;)? ) => {
+                $(
+                    #[test]
+                    fn $name() {
+                        let error = $src.parse::<Color>().unwrap_err();
+                        assert_eq!(error.input(), $src);
+                        assert_eq!(error.kind(), ColorParseErrorKind::$kind);
+                        assert_eq!(error.suggestion(), $suggestion);
+                    }
+                )*
+            };
+        }
+
+        make_error_test!(
+            typo_dash: "bright-red" => UnknownName, Some("bright red");
+            typo_letter: "yelow" => UnknownName, Some("yellow");
+            typo_css: "rebecapurple" => UnknownName, Some("rebeccapurple");
+            typo_upper: "Gren" => UnknownName, Some("green");
+            typo_function: "rgv(1 2 3)" => UnknownName, Some("rgb");
+            far_from_everything: "zorglub" => UnknownName, None;
+            empty: "" => UnknownName, None;
+            hex_length: "#abcde" => InvalidHexLength, None;
+            hex_digit: "#abcxyz" => InvalidHexDigit, None;
+            hex_sign: "#+ab" => InvalidHexDigit, None;
+            rgb_out_of_range: "rgb(300 0 0)" => OutOfRange, None;
+            ansi_out_of_range: "ansi(256)" => OutOfRange, None;
+            bare_out_of_range: "256" => OutOfRange, None;
+            bare_negative: "-1" => OutOfRange, None;
+            bare_garbage: "12ab" => InvalidSyntax, None;
+            unclosed_function: "rgb(1 2 3" => InvalidSyntax, None;
+            missing_arguments: "hsl(30, 100%)" => InvalidSyntax, None;
+        );
+
+        #[test]
+        fn error_display() {
+            let error = "bright-red".parse::<Color>().unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                r#"unknown color "bright-red", did you mean "bright red"?"#
+            );
+            let error = "#abcde".parse::<Color>().unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                r##"invalid hex color "#abcde": expected 3, 4, 6 or 8 digits"##
+            );
+        }
+
+        #[test]
+        fn edit_distances() {
+            assert_eq!(edit_distance("", ""), 0);
+            assert_eq!(edit_distance("abc", ""), 3);
+            assert_eq!(edit_distance("kitten", "sitting"), 3);
+            assert_eq!(edit_distance("bright-red", "bright red"), 1);
+            assert_eq!(edit_distance("bleu", "blue"), 1);
+        }
+    }
+
+    mod display {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn plain_color() -> impl Strategy<Value = Color> {
+            prop_oneof![
+                (0..16_usize).prop_map(|index| BASIC_COLORS[index]),
+                Just(Color::Default),
+                any::<u8>().prop_map(Color::AnsiColor),
+                any::<(u8, u8, u8)>().prop_map(|(r, g, b)| Color::TrueColor { r, g, b }),
+            ]
+        }
+
+        fn color() -> impl Strategy<Value = Color> {
+            prop_oneof![
+                plain_color(),
+                (plain_color(), plain_color())
+                    .prop_map(|(light, dark)| AdaptiveColor::new(light, dark).into()),
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn round_trip(color in color()) {
+                prop_assert_eq!(color.to_string().parse::<Color>(), Ok(color));
+            }
+        }
+
+        #[test]
+        fn names() {
+            for (name, color) in COLOR_NAMES {
+                if name != "purple" {
+                    assert_eq!(color.to_string(), name);
+                }
+            }
+            assert_eq!(Color::Magenta.to_string(), "magenta");
+        }
+
+        #[test]
+        fn adaptive() {
+            let adaptive = Color::from(AdaptiveColor::new(
+                Color::AnsiColor(0),
+                Color::TrueColor {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                },
+            ));
+            assert_eq!(adaptive.to_string(), "light-dark(ansi(0), #ffffff)");
+            assert_eq!(
+                "light-dark(rgb(0, 0, 0), white)".parse(),
+                Ok(Color::from(AdaptiveColor::new(
+                    Color::TrueColor { r: 0, g: 0, b: 0 },
+                    Color::White
+                )))
+            );
+        }
+
+        #[test]
+        fn invalid_light_dark() {
+            let kind = |s: &str| s.parse::<Color>().unwrap_err().kind();
+            assert_eq!(kind("light-dark(red)"), ColorParseErrorKind::InvalidSyntax);
+            assert_eq!(
+                kind("light-dark(red, blue"),
+                ColorParseErrorKind::InvalidSyntax
+            );
+            assert_eq!(
+                kind("light-dark(red, bleu)"),
+                ColorParseErrorKind::UnknownName
+            );
         }
     }
 
@@ -394,7 +1258,8 @@ mod tests {
                         g: $g,
                         b: $b,
                     };
-                    let actual = true_color.closest_color_euclidean();
+                    let actual =
+                        true_color.closest_color(DistanceMetric::Euclidean, &Palette::XTERM);
                     assert_eq!(actual, $expected);
                 }
             };
@@ -418,4 +1283,482 @@ mod tests {
         make_euclidean_distance_test! { almost_cyan: (10, 215, 215), Color::Cyan }
         make_euclidean_distance_test! { almost_white: (209, 209, 229), Color::White }
     }
+
+    mod closest_perceptual {
+        use super::*;
+
+        #[test]
+        fn exact_colors_are_kept() {
+            let palettes = [Palette::XTERM, Palette::VGA, Palette::SOLARIZED];
+            for metric in [DistanceMetric::OkLab, DistanceMetric::Ciede2000] {
+                for palette in &palettes {
+                    for color in BASIC_COLORS {
+                        let (r, g, b) = color.rgb_in(palette);
+                        let true_color = Color::TrueColor { r, g, b };
+                        let closest = true_color.closest_color(metric, palette);
+                        assert_eq!(closest, color, "{metric:?} {palette:?}");
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn palette_is_used() {
+            let light_blue = Color::TrueColor {
+                r: 40,
+                g: 140,
+                b: 210,
+            };
+            let metric = DistanceMetric::OkLab;
+            assert_eq!(
+                light_blue.closest_color(metric, &Palette::XTERM),
+                Color::BrightBlack
+            );
+            assert_eq!(
+                light_blue.closest_color(metric, &Palette::SOLARIZED),
+                Color::Blue
+            );
+        }
+
+        #[test]
+        fn dark_orange_keeps_its_hue() {
+            let dark_orange = Color::TrueColor {
+                r: 205,
+                g: 110,
+                b: 30,
+            };
+            assert_eq!(
+                dark_orange.closest_color(DistanceMetric::Euclidean, &Palette::XTERM),
+                Color::Yellow
+            );
+            assert_eq!(
+                dark_orange.closest_color(DistanceMetric::OkLab, &Palette::XTERM),
+                Color::BrightRed
+            );
+            assert_eq!(
+                dark_orange.closest_color(DistanceMetric::Ciede2000, &Palette::XTERM),
+                Color::BrightRed
+            );
+        }
+
+        #[test]
+        fn desaturated_blue_keeps_its_hue() {
+            let desaturated_blue = Color::TrueColor {
+                r: 100,
+                g: 110,
+                b: 160,
+            };
+            assert_eq!(
+                desaturated_blue.closest_color(DistanceMetric::Euclidean, &Palette::XTERM),
+                Color::BrightBlack
+            );
+            assert_eq!(
+                desaturated_blue.closest_color(DistanceMetric::Ciede2000, &Palette::XTERM),
+                Color::BrightBlue
+            );
+        }
+
+        #[test]
+        fn ansi256_uses_the_metric() {
+            let desaturated_blue = Color::TrueColor {
+                r: 100,
+                g: 110,
+                b: 160,
+            };
+            assert_eq!(
+                desaturated_blue.closest_ansi256(DistanceMetric::Euclidean, &Palette::XTERM),
+                Color::AnsiColor(61)
+            );
+            assert_eq!(
+                desaturated_blue.closest_ansi256(DistanceMetric::OkLab, &Palette::XTERM),
+                Color::AnsiColor(60)
+            );
+        }
+    }
+
+    mod manipulation {
+        use super::*;
+
+        fn oklch(color: Color) -> OkLch {
+            OkLch::from_rgb(color.rgb())
+        }
+
+        const ORANGE: Color = Color::TrueColor {
+            r: 255,
+            g: 136,
+            b: 0,
+        };
+
+        #[test]
+        fn lighten_and_darken() {
+            let lighter = ORANGE.lighten(0.1);
+            let darker = ORANGE.darken(0.1);
+            assert!(oklch(lighter).l > oklch(ORANGE).l);
+            assert!(oklch(darker).l < oklch(ORANGE).l);
+            assert!((oklch(darker).l - (oklch(ORANGE).l - 0.1)).abs() < 0.01);
+            assert!((oklch(darker).h - oklch(ORANGE).h).abs() < 2.0);
+        }
+
+        #[test]
+        fn lighten_saturates_at_white() {
+            let white = Color::TrueColor {
+                r: 255,
+                g: 255,
+                b: 255,
+            };
+            assert_eq!(ORANGE.lighten(2.0), white);
+            assert_eq!(ORANGE.darken(2.0), Color::TrueColor { r: 0, g: 0, b: 0 });
+        }
+
+        #[test]
+        fn saturate_and_desaturate() {
+            assert!(oklch(ORANGE.desaturate(0.5)).c < oklch(ORANGE).c);
+            let muted = ORANGE.desaturate(0.5);
+            assert!(oklch(muted.saturate(0.3)).c > oklch(muted).c);
+            let gray = ORANGE.desaturate(1.0);
+            let (r, g, b) = gray.rgb();
+            assert!(r.abs_diff(g) <= 1 && g.abs_diff(b) <= 1);
+            assert_eq!(ORANGE.desaturate(2.0), gray);
+        }
+
+        #[test]
+        fn rotate_hue() {
+            let rotated = ORANGE.rotate_hue(120.0);
+            let delta = (oklch(rotated).h - oklch(ORANGE).h).rem_euclid(360.0);
+            assert!((delta - 120.0).abs() < 3.0, "{delta}");
+            assert_eq!(ORANGE.rotate_hue(360.0), ORANGE);
+        }
+
+        #[test]
+        fn complement() {
+            let delta = (oklch(ORANGE.complement()).h - oklch(ORANGE).h).rem_euclid(360.0);
+            assert!((delta - 180.0).abs() < 3.0, "{delta}");
+        }
+
+        #[test]
+        fn mix() {
+            let black = Color::TrueColor { r: 0, g: 0, b: 0 };
+            let white = Color::TrueColor {
+                r: 255,
+                g: 255,
+                b: 255,
+            };
+            assert_eq!(black.mix(white, 0.0), black);
+            assert_eq!(black.mix(white, 1.0), white);
+            assert_eq!(black.mix(white, 2.0), white);
+            let (r, g, b) = black.mix(white, 0.5).rgb();
+            assert_eq!((r, r), (g, b));
+            assert!((90..=110).contains(&r), "{r}");
+        }
+
+        #[test]
+        fn invert() {
+            assert_eq!(
+                Color::Black.invert(),
+                Color::TrueColor {
+                    r: 255,
+                    g: 255,
+                    b: 255
+                }
+            );
+            assert_eq!(ORANGE.invert().invert(), ORANGE);
+        }
+
+        #[test]
+        fn basic_and_ansi_colors_are_resolved() {
+            assert_eq!(
+                Color::Red.lighten(0.0),
+                Color::TrueColor { r: 205, g: 0, b: 0 }
+            );
+            assert_eq!(
+                Color::AnsiColor(1).lighten(0.0),
+                Color::TrueColor { r: 205, g: 0, b: 0 }
+            );
+            assert_eq!(
+                Color::AnsiColor(208).lighten(0.0),
+                Color::TrueColor {
+                    r: 255,
+                    g: 135,
+                    b: 0
+                }
+            );
+            assert_eq!(
+                control::with_palette(Palette::VGA, || Color::Red.lighten(0.0)),
+                Color::TrueColor { r: 170, g: 0, b: 0 }
+            );
+            assert_eq!(
+                Color::AnsiColor(244).invert(),
+                Color::TrueColor {
+                    r: 127,
+                    g: 127,
+                    b: 127
+                }
+            );
+        }
+    }
+
+    mod contrast {
+        use super::*;
+
+        #[test]
+        fn luminance() {
+            assert!(Color::Black.relative_luminance().abs() < 1e-6);
+            assert!((Color::BrightWhite.relative_luminance() - 1.0).abs() < 1e-6);
+            let gray = Color::TrueColor {
+                r: 119,
+                g: 119,
+                b: 119,
+            };
+            assert!((gray.relative_luminance() - 0.184).abs() < 0.001);
+        }
+
+        #[test]
+        fn contrast_ratio() {
+            let ratio = Color::Black.contrast_ratio(&Color::BrightWhite);
+            assert!((ratio - 21.0).abs() < 1e-4, "{ratio}");
+            let ratio = Color::BrightWhite.contrast_ratio(&Color::Black);
+            assert!((ratio - 21.0).abs() < 1e-4, "{ratio}");
+            assert!((Color::Red.contrast_ratio(&Color::Red) - 1.0).abs() < 1e-6);
+            // #777 on white is the classic example of a gray just below 4.5
+            let gray = Color::TrueColor {
+                r: 119,
+                g: 119,
+                b: 119,
+            };
+            let ratio = gray.contrast_ratio(&Color::BrightWhite);
+            assert!((ratio - 4.48).abs() < 0.01, "{ratio}");
+        }
+
+        #[test]
+        fn readable_on() {
+            assert_eq!(Color::readable_on(Color::Black, &[]), None);
+            assert_eq!(
+                Color::readable_on(Color::Black, &[Color::Blue, Color::BrightYellow]),
+                Some(Color::BrightYellow)
+            );
+        }
+
+        #[test]
+        fn black_or_white_on() {
+            assert_eq!(Color::black_or_white_on(Color::BrightWhite), Color::Black);
+            assert_eq!(Color::black_or_white_on(Color::Yellow), Color::Black);
+            assert_eq!(Color::black_or_white_on(Color::Black), Color::BrightWhite);
+            assert_eq!(Color::black_or_white_on(Color::Blue), Color::BrightWhite);
+        }
+
+        #[test]
+        fn ensure_contrast() {
+            let bg = Color::BrightWhite;
+            assert_eq!(Color::Black.ensure_contrast(bg, 4.5), Color::Black);
+
+            let fg = Color::BrightYellow.ensure_contrast(bg, 4.5);
+            let ratio = fg.contrast_ratio(&bg);
+            assert!(ratio >= 4.5, "{ratio}");
+            // only as much as needed
+            assert!(ratio < 5.0, "{ratio}");
+
+            let fg = Color::Blue.ensure_contrast(Color::Black, 7.0);
+            assert!(fg.contrast_ratio(&Color::Black) >= 7.0);
+        }
+
+        #[test]
+        fn ensure_contrast_unreachable() {
+            let bg = Color::TrueColor {
+                r: 119,
+                g: 119,
+                b: 119,
+            };
+            let fg = Color::Red.ensure_contrast(bg, 21.0);
+            assert_eq!(fg, Color::TrueColor { r: 0, g: 0, b: 0 });
+        }
+    }
+
+    mod ansi_color {
+        use super::*;
+
+        macro_rules! make_to_rgb_test {
+            ( $test:ident : $index:literal, ( $r:literal, $g: literal, $b:literal ) ) => {
+                #[test]
+                fn $test() {
+                    let color = Color::AnsiColor($index);
+                    assert_eq!(color.to_rgb(), CustomColor::new($r, $g, $b));
+                    assert_eq!(
+                        color.into_truecolor(),
+                        Color::TrueColor {
+                            r: $r,
+                            g: $g,
+                            b: $b
+                        }
+                    );
+                }
+            };
+        }
+
+        make_to_rgb_test! { system_black: 0, (0, 0, 0) }
+        make_to_rgb_test! { system_blue: 4, (0, 0, 238) }
+        make_to_rgb_test! { system_bright_white: 15, (255, 255, 255) }
+        make_to_rgb_test! { cube_black: 16, (0, 0, 0) }
+        make_to_rgb_test! { cube_blue: 21, (0, 0, 255) }
+        make_to_rgb_test! { cube_orange: 208, (255, 135, 0) }
+        make_to_rgb_test! { cube_white: 231, (255, 255, 255) }
+        make_to_rgb_test! { gray_darkest: 232, (8, 8, 8) }
+        make_to_rgb_test! { gray_middle: 244, (128, 128, 128) }
+        make_to_rgb_test! { gray_lightest: 255, (238, 238, 238) }
+
+        #[test]
+        fn basic_colors_to_rgb() {
+            assert_eq!(Color::Red.to_rgb(), CustomColor::new(205, 0, 0));
+            assert_eq!(
+                Color::Red.into_truecolor(),
+                Color::TrueColor { r: 205, g: 0, b: 0 }
+            );
+            let orange = Color::TrueColor {
+                r: 255,
+                g: 136,
+                b: 0,
+            };
+            assert_eq!(orange.into_truecolor(), orange);
+        }
+
+        #[test]
+        fn closest_color() {
+            let closest = |index| {
+                Color::AnsiColor(index).closest_color(DistanceMetric::OkLab, &Palette::XTERM)
+            };
+            for (index, color) in BASIC_COLORS.into_iter().enumerate() {
+                assert_eq!(closest(u8::try_from(index).unwrap()), color);
+            }
+            assert_eq!(closest(16), Color::Black);
+            assert_eq!(closest(196), Color::BrightRed);
+            assert_eq!(closest(231), Color::BrightWhite);
+            assert_eq!(closest(244), Color::BrightBlack);
+        }
+
+        #[test]
+        fn color_support_detection() {
+            let detect = ColorSupport::detect;
+            // e.g. the Windows console
+            assert_eq!(detect(None, None), ColorSupport::Ansi256);
+            for term in ["xterm", "screen", "tmux", "xterm-256color"] {
+                assert_eq!(detect(None, Some(term)), ColorSupport::Ansi256, "{term}");
+            }
+            for term in ["linux", "dumb", "vt100"] {
+                assert_eq!(detect(None, Some(term)), ColorSupport::Basic, "{term}");
+            }
+            let truecolor = ColorSupport::TrueColor;
+            assert_eq!(detect(Some("truecolor"), Some("xterm")), truecolor);
+            assert_eq!(detect(Some("24bit"), None), truecolor);
+        }
+
+        #[test]
+        fn downgraded_only_without_256_colors() {
+            let orange = Color::AnsiColor(208);
+            let fg_str = |support| control::with_color_support(support, || orange.to_fg_str());
+            assert_eq!(fg_str(ColorSupport::detect(None, None)), "38;5;208");
+            assert_eq!(
+                fg_str(ColorSupport::detect(None, Some("xterm"))),
+                "38;5;208"
+            );
+            assert_eq!(fg_str(ColorSupport::TrueColor), "38;5;208");
+            assert_eq!(fg_str(ColorSupport::Basic), "91");
+        }
+    }
+
+    mod adaptive {
+        use super::*;
+
+        const COLORS: [Color; 5] = [
+            Color::Red,
+            Color::BrightWhite,
+            Color::AnsiColor(0),
+            Color::AnsiColor(208),
+            Color::TrueColor { r: 1, g: 2, b: 3 },
+        ];
+
+        #[test]
+        fn variants_are_kept() {
+            for light in COLORS {
+                for dark in COLORS {
+                    let adaptive = AdaptiveColor::new(light, dark);
+                    assert_eq!(adaptive.light(), light);
+                    assert_eq!(adaptive.dark(), dark);
+                    assert_eq!(adaptive.for_theme(Theme::Light), light);
+                    assert_eq!(adaptive.for_theme(Theme::Dark), dark);
+                }
+            }
+        }
+
+        #[test]
+        fn nested_colors_are_flattened() {
+            let inner = AdaptiveColor::new(Color::Black, Color::White);
+            let outer = AdaptiveColor::new(inner.into(), inner.into());
+            assert_eq!(outer, inner);
+        }
+
+        #[test]
+        fn debug() {
+            let adaptive = AdaptiveColor::new(Color::Black, Color::AnsiColor(7));
+            assert_eq!(
+                format!("{adaptive:?}"),
+                "AdaptiveColor { light: Black, dark: AnsiColor(7) }"
+            );
+        }
+
+        #[test]
+        fn theme_from_background() {
+            assert_eq!(Theme::from_background(Color::Black), Theme::Dark);
+            assert_eq!(Theme::from_background(Color::BrightWhite), Theme::Light);
+            let solarized_dark = Color::TrueColor { r: 0, g: 43, b: 54 };
+            assert_eq!(Theme::from_background(solarized_dark), Theme::Dark);
+            let solarized_light = Color::TrueColor {
+                r: 253,
+                g: 246,
+                b: 227,
+            };
+            assert_eq!(Theme::from_background(solarized_light), Theme::Light);
+        }
+    }
+
+    mod closest_ansi256 {
+        use super::*;
+
+        macro_rules! make_ansi256_test {
+            ( $test:ident : ( $r:literal, $g: literal, $b:literal ), $expected:expr ) => {
+                #[test]
+                fn $test() {
+                    let true_color = Color::TrueColor {
+                        r: $r,
+                        g: $g,
+                        b: $b,
+                    };
+                    let actual =
+                        true_color.closest_ansi256(DistanceMetric::Euclidean, &Palette::XTERM);
+                    assert_eq!(actual, Color::AnsiColor($expected));
+                }
+            };
+        }
+
+        make_ansi256_test! { cube_black: (0, 0, 0), 16 }
+        make_ansi256_test! { cube_white: (255, 255, 255), 231 }
+        make_ansi256_test! { cube_red: (255, 0, 0), 196 }
+        make_ansi256_test! { cube_orange: (255, 135, 0), 208 }
+        make_ansi256_test! { cube_purple: (95, 0, 135), 54 }
+        make_ansi256_test! { almost_orange: (250, 130, 10), 208 }
+        make_ansi256_test! { almost_teal: (0, 120, 120), 30 }
+        make_ansi256_test! { gray_darkest: (8, 8, 8), 232 }
+        make_ansi256_test! { gray_middle: (128, 128, 128), 244 }
+        make_ansi256_test! { almost_gray: (100, 102, 98), 241 }
+        make_ansi256_test! { gray_lightest: (238, 238, 238), 255 }
+
+        #[test]
+        fn leaves_other_colors_alone() {
+            let metric = DistanceMetric::Euclidean;
+            let palette = &Palette::XTERM;
+            assert_eq!(Color::Red.closest_ansi256(metric, palette), Color::Red);
+            assert_eq!(
+                Color::AnsiColor(42).closest_ansi256(metric, palette),
+                Color::AnsiColor(42)
+            );
+        }
+    }
 }
@@ -0,0 +1,343 @@
+//! Conversions between sRGB and the perceptual color spaces used to compare
+//! and manipulate colors.
+#![allow(clippy::many_single_char_names, clippy::suboptimal_flops)]
+
+/// A color in the `OKLab` color space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OkLab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+/// A color in the CIE L*a*b* color space (D65 white point).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+/// Converts an 8 bit sRGB channel to linear light.
+pub fn srgb_to_linear(c: u8) -> f32 {
+    let c = f32::from(c) / 255.0;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear light channel back to 8 bit sRGB, clamping it to the
+/// gamut.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn linear_to_srgb(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+impl OkLab {
+    pub fn from_rgb((r, g, b): (u8, u8, u8)) -> Self {
+        let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+
+        let l = 0.412_221_47 * r + 0.536_332_55 * g + 0.051_457_565 * b;
+        let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_169_38 * b;
+        let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+        Self {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        }
+    }
+
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        let (r, g, b) = self.to_linear_rgb();
+        (linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+    }
+
+    fn to_linear_rgb(self) -> (f32, f32, f32) {
+        let l = self.l + 0.396_337_78 * self.a + 0.215_803_76 * self.b;
+        let m = self.l - 0.105_561_346 * self.a - 0.063_854_17 * self.b;
+        let s = self.l - 0.089_484_18 * self.a - 1.291_485_5 * self.b;
+
+        let (l, m, s) = (l.powi(3), m.powi(3), s.powi(3));
+
+        (
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_759_05 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_4 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        )
+    }
+
+    fn is_in_gamut(self) -> bool {
+        const EPSILON: f32 = 1e-4;
+        let rgb: [f32; 3] = self.to_linear_rgb().into();
+        rgb.iter().all(|c| (-EPSILON..=1.0 + EPSILON).contains(c))
+    }
+
+    /// Linear interpolation between two colors, `t` going from 0 (`self`)
+    /// to 1 (`other`).
+    pub fn mix(self, other: Self, t: f32) -> Self {
+        Self {
+            l: self.l + (other.l - self.l) * t,
+            a: self.a + (other.a - self.a) * t,
+            b: self.b + (other.b - self.b) * t,
+        }
+    }
+
+    /// Squared euclidean distance between two colors.
+    pub fn distance_squared(self, other: Self) -> f32 {
+        let dl = self.l - other.l;
+        let da = self.a - other.a;
+        let db = self.b - other.b;
+        dl * dl + da * da + db * db
+    }
+}
+
+/// A color in the `OKLCH` color space, the polar form of `OKLab`. The hue is
+/// in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OkLch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+}
+
+impl OkLch {
+    pub fn from_rgb(rgb: (u8, u8, u8)) -> Self {
+        let OkLab { l, a, b } = OkLab::from_rgb(rgb);
+        Self {
+            l,
+            c: a.hypot(b),
+            h: b.atan2(a).to_degrees().rem_euclid(360.0),
+        }
+    }
+
+    fn to_oklab(self) -> OkLab {
+        let (sin, cos) = self.h.to_radians().sin_cos();
+        OkLab {
+            l: self.l,
+            a: self.c * cos,
+            b: self.c * sin,
+        }
+    }
+
+    /// Converts the color to sRGB. Colors outside of the sRGB gamut have
+    /// their chroma reduced until they fit, which preserves their lightness
+    /// and hue.
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        let color = Self {
+            l: self.l.clamp(0.0, 1.0),
+            c: self.c.max(0.0),
+            h: self.h,
+        };
+        if color.to_oklab().is_in_gamut() {
+            return color.to_oklab().to_rgb();
+        }
+
+        let (mut low, mut high) = (0.0, color.c);
+        for _ in 0..16 {
+            let c = (low + high) / 2.0;
+            let candidate = Self { c, ..color };
+            if candidate.to_oklab().is_in_gamut() {
+                low = c;
+            } else {
+                high = c;
+            }
+        }
+        Self { c: low, ..color }.to_oklab().to_rgb()
+    }
+}
+
+impl Lab {
+    pub fn from_rgb((r, g, b): (u8, u8, u8)) -> Self {
+        // D65 reference white
+        const XN: f32 = 0.950_47;
+        const YN: f32 = 1.0;
+        const ZN: f32 = 1.088_83;
+
+        fn f(t: f32) -> f32 {
+            const DELTA: f32 = 6.0 / 29.0;
+            if t > DELTA * DELTA * DELTA {
+                t.cbrt()
+            } else {
+                t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+            }
+        }
+
+        let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+
+        let x = 0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b;
+        let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b;
+        let z = 0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b;
+
+        let (fx, fy, fz) = (f(x / XN), f(y / YN), f(z / ZN));
+
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    /// The CIEDE2000 color difference between two colors.
+    pub fn ciede2000(self, other: Self) -> f32 {
+        let pow7 = |x: f32| x.powi(7);
+        let twenty_five_pow7 = pow7(25.0);
+
+        let c1 = self.a.hypot(self.b);
+        let c2 = other.a.hypot(other.b);
+        let c_mean = (c1 + c2) / 2.0;
+        let g = 0.5 * (1.0 - (pow7(c_mean) / (pow7(c_mean) + twenty_five_pow7)).sqrt());
+
+        let a1 = (1.0 + g) * self.a;
+        let a2 = (1.0 + g) * other.a;
+        let c1 = a1.hypot(self.b);
+        let c2 = a2.hypot(other.b);
+
+        let hue = |b: f32, a: f32| {
+            if a == 0.0 && b == 0.0 {
+                0.0
+            } else {
+                b.atan2(a).to_degrees().rem_euclid(360.0)
+            }
+        };
+        let h1 = hue(self.b, a1);
+        let h2 = hue(other.b, a2);
+
+        let dl = other.l - self.l;
+        let dc = c2 - c1;
+        let dh = if c1 * c2 == 0.0 {
+            0.0
+        } else if (h2 - h1).abs() <= 180.0 {
+            h2 - h1
+        } else if h2 - h1 > 180.0 {
+            h2 - h1 - 360.0
+        } else {
+            h2 - h1 + 360.0
+        };
+        let dh = 2.0 * (c1 * c2).sqrt() * (dh / 2.0).to_radians().sin();
+
+        let l_mean = (self.l + other.l) / 2.0;
+        let c_mean = (c1 + c2) / 2.0;
+        let h_mean = if c1 * c2 == 0.0 {
+            h1 + h2
+        } else if (h1 - h2).abs() <= 180.0 {
+            (h1 + h2) / 2.0
+        } else if h1 + h2 < 360.0 {
+            (h1 + h2 + 360.0) / 2.0
+        } else {
+            (h1 + h2 - 360.0) / 2.0
+        };
+
+        let t = 1.0 - 0.17 * (h_mean - 30.0).to_radians().cos()
+            + 0.24 * (2.0 * h_mean).to_radians().cos()
+            + 0.32 * (3.0 * h_mean + 6.0).to_radians().cos()
+            - 0.20 * (4.0 * h_mean - 63.0).to_radians().cos();
+
+        let d_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+        let r_c = 2.0 * (pow7(c_mean) / (pow7(c_mean) + twenty_five_pow7)).sqrt();
+        let l_mean_50 = (l_mean - 50.0).powi(2);
+        let s_l = 1.0 + (0.015 * l_mean_50) / (20.0 + l_mean_50).sqrt();
+        let s_c = 1.0 + 0.045 * c_mean;
+        let s_h = 1.0 + 0.015 * c_mean * t;
+        let r_t = -(2.0 * d_theta).to_radians().sin() * r_c;
+
+        let (l_term, c_term, h_term) = (dl / s_l, dc / s_c, dh / s_h);
+        (l_term * l_term + c_term * c_term + h_term * h_term + r_t * c_term * h_term).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oklab_roundtrip() {
+        for rgb in [(0, 0, 0), (255, 255, 255), (255, 136, 0), (12, 200, 97)] {
+            assert_eq!(OkLab::from_rgb(rgb).to_rgb(), rgb);
+        }
+    }
+
+    #[test]
+    fn oklch_roundtrip() {
+        for rgb in [(0, 0, 0), (255, 255, 255), (255, 136, 0), (12, 200, 97)] {
+            assert_eq!(OkLch::from_rgb(rgb).to_rgb(), rgb);
+        }
+    }
+
+    #[test]
+    fn oklch_gamut_mapping_keeps_lightness() {
+        let out_of_gamut = OkLch {
+            l: 0.9,
+            c: 0.4,
+            h: 30.0,
+        };
+        let mapped = OkLch::from_rgb(out_of_gamut.to_rgb());
+        assert!((mapped.l - 0.9).abs() < 0.01);
+        assert!(mapped.c < 0.4);
+    }
+
+    #[test]
+    fn oklab_white() {
+        let white = OkLab::from_rgb((255, 255, 255));
+        assert!((white.l - 1.0).abs() < 1e-3);
+        assert!(white.a.abs() < 1e-3);
+        assert!(white.b.abs() < 1e-3);
+    }
+
+    #[test]
+    fn lab_red() {
+        let red = Lab::from_rgb((255, 0, 0));
+        assert!((red.l - 53.24).abs() < 0.1);
+        assert!((red.a - 80.09).abs() < 0.1);
+        assert!((red.b - 67.20).abs() < 0.1);
+    }
+
+    #[test]
+    fn ciede2000_reference_pairs() {
+        // Pairs from Sharma, Wu and Dalal, "The CIEDE2000 Color-Difference
+        // Formula: Implementation Notes, Supplementary Test Data, and
+        // Mathematical Observations".
+        let pairs = [
+            ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+            ((50.0, -1.3802, -84.2814), (50.0, 0.0, -82.7485), 1.0000),
+            ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+            (
+                (60.2574, -34.0099, 36.2677),
+                (60.4626, -34.1751, 39.4387),
+                1.2644,
+            ),
+            (
+                (22.7233, 20.0904, -46.6940),
+                (23.0331, 14.9730, -42.5619),
+                2.0373,
+            ),
+        ];
+        for ((l1, a1, b1), (l2, a2, b2), expected) in pairs {
+            let lab1 = Lab {
+                l: l1,
+                a: a1,
+                b: b1,
+            };
+            let lab2 = Lab {
+                l: l2,
+                a: a2,
+                b: b2,
+            };
+            let delta = lab1.ciede2000(lab2);
+            assert!(
+                (delta - expected).abs() < 1e-3,
+                "expected {expected}, got {delta}"
+            );
+        }
+    }
+}
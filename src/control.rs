@@ -1,10 +1,18 @@
 //! A couple of functions to enable and disable coloring.
 
+pub use crate::query::{
+    query_ansi_color, query_background, query_foreground, query_palette, QueryTerminal,
+    TerminalQuery,
+};
+use crate::{
+    ColorSupport, CvdTransform, Deficiency, DistanceMetric, Palette, QueryError, ResetMode, Theme,
+};
+use std::cell::{Cell, RefCell};
 use std::default::Default;
 use std::env;
 use std::io::{self, IsTerminal};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{LazyLock, PoisonError, RwLock};
 
 /// Sets a flag to the console to use a virtual terminal environment.
 ///
@@ -85,6 +93,586 @@ pub fn unset_override() {
 /// The persistent [`ShouldColorize`].
 pub static SHOULD_COLORIZE: LazyLock<ShouldColorize> = LazyLock::new(ShouldColorize::from_env);
 
+thread_local! {
+    static SCOPED_OVERRIDE: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Forces colored to always or never colorize what is rendered on the
+/// current thread while `f` runs, without changing the override set with
+/// [`set_override`].
+///
+/// ```rust
+/// use colored::{control, Colorize};
+/// let text = "text".red();
+/// assert_eq!(control::with_override(false, || text.to_string()), "text");
+/// let rendered = control::with_override(true, || text.to_string());
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(rendered, "\x1b[31mtext\x1b[0m");
+/// # }
+/// ```
+pub fn with_override<R>(override_colorize: bool, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous override, even if `f` panics.
+    struct Restore(Option<bool>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_OVERRIDE.set(self.0);
+        }
+    }
+
+    let _restore = Restore(SCOPED_OVERRIDE.replace(Some(override_colorize)));
+    f()
+}
+
+/// Checks if coloring should occur: the override given to [`with_override`]
+/// when called from its closure, or else [`SHOULD_COLORIZE`].
+#[cfg(not(feature = "no-color"))]
+pub(crate) fn should_colorize() -> bool {
+    SCOPED_OVERRIDE
+        .get()
+        .unwrap_or_else(|| SHOULD_COLORIZE.should_colorize())
+}
+
+static DISTANCE_METRIC: AtomicU8 = AtomicU8::new(DistanceMetric::OkLab as u8);
+
+/// Sets the [`DistanceMetric`] used to find the closest color when a color
+/// has to be downgraded to what the terminal supports.
+///
+/// ```rust
+/// use colored::{control, DistanceMetric};
+/// control::set_distance_metric(DistanceMetric::Ciede2000);
+/// assert_eq!(control::distance_metric(), DistanceMetric::Ciede2000);
+/// ```
+pub fn set_distance_metric(metric: DistanceMetric) {
+    DISTANCE_METRIC.store(metric as u8, Ordering::Relaxed);
+}
+
+/// Gets the [`DistanceMetric`] currently used to downgrade colors.
+pub fn distance_metric() -> DistanceMetric {
+    match DISTANCE_METRIC.load(Ordering::Relaxed) {
+        x if x == DistanceMetric::Euclidean as u8 => DistanceMetric::Euclidean,
+        x if x == DistanceMetric::Ciede2000 as u8 => DistanceMetric::Ciede2000,
+        _ => DistanceMetric::OkLab,
+    }
+}
+
+/// The color support set with [`set_color_support`]: 0 if unset, or else 1
+/// plus the [`ColorSupport`].
+static COLOR_SUPPORT: AtomicU8 = AtomicU8::new(0);
+
+static DETECTED_COLOR_SUPPORT: LazyLock<ColorSupport> = LazyLock::new(|| {
+    let var = |name| env::var(name).ok();
+    ColorSupport::detect(var("COLORTERM").as_deref(), var("TERM").as_deref())
+});
+
+thread_local! {
+    static SCOPED_COLOR_SUPPORT: Cell<Option<ColorSupport>> = const { Cell::new(None) };
+}
+
+/// Sets the [`ColorSupport`] of the terminal, instead of detecting it from
+/// the `COLORTERM` and `TERM` environment variables.
+///
+/// ```rust
+/// use colored::{control, Color, ColorSupport, Colorize};
+/// # control::set_override(true);
+/// control::set_color_support(ColorSupport::Basic);
+/// let orange = "orange".color(Color::AnsiColor(208));
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(orange.to_string(), "\x1b[91morange\x1b[0m");
+/// # }
+/// control::set_color_support(ColorSupport::Ansi256);
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(orange.to_string(), "\x1b[38;5;208morange\x1b[0m");
+/// # }
+/// ```
+pub fn set_color_support(support: ColorSupport) {
+    COLOR_SUPPORT.store(support as u8 + 1, Ordering::Relaxed);
+}
+
+/// Goes back to detecting the [`ColorSupport`] of the terminal.
+pub fn unset_color_support() {
+    COLOR_SUPPORT.store(0, Ordering::Relaxed);
+}
+
+/// Gets the [`ColorSupport`] currently used: the one given to
+/// [`with_color_support`] when called from its closure, or else the one set
+/// with [`set_color_support`], or else the detected one.
+///
+/// It is detected once, the first time it is needed.
+pub fn color_support() -> ColorSupport {
+    if let Some(support) = SCOPED_COLOR_SUPPORT.get() {
+        return support;
+    }
+    match COLOR_SUPPORT.load(Ordering::Relaxed) {
+        0 => *DETECTED_COLOR_SUPPORT,
+        x if x == ColorSupport::Basic as u8 + 1 => ColorSupport::Basic,
+        x if x == ColorSupport::Ansi256 as u8 + 1 => ColorSupport::Ansi256,
+        _ => ColorSupport::TrueColor,
+    }
+}
+
+/// Uses a [`ColorSupport`] for everything rendered on the current thread
+/// while `f` runs, without changing the one set with [`set_color_support`].
+///
+/// ```rust
+/// use colored::{control, ColorSupport, Colorize};
+/// # control::set_override(true);
+/// let text = "text".truecolor(255, 0, 0);
+/// let rendered = control::with_color_support(ColorSupport::TrueColor, || text.to_string());
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(rendered, "\x1b[38;2;255;0;0mtext\x1b[0m");
+/// # }
+/// ```
+pub fn with_color_support<R>(support: ColorSupport, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous color support, even if `f` panics.
+    struct Restore(Option<ColorSupport>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_COLOR_SUPPORT.set(self.0);
+        }
+    }
+
+    let _restore = Restore(SCOPED_COLOR_SUPPORT.replace(Some(support)));
+    f()
+}
+
+static RESET_MODE: AtomicU8 = AtomicU8::new(ResetMode::Full as u8);
+
+thread_local! {
+    static SCOPED_RESET_MODE: Cell<Option<ResetMode>> = const { Cell::new(None) };
+}
+
+/// Sets the [`ResetMode`], how colored strings turn off their attributes
+/// after their text.
+///
+/// ```rust
+/// use colored::{control, ResetMode};
+/// control::set_reset_mode(ResetMode::Targeted);
+/// assert_eq!(control::reset_mode(), ResetMode::Targeted);
+/// # control::set_reset_mode(ResetMode::Full);
+/// ```
+pub fn set_reset_mode(mode: ResetMode) {
+    RESET_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Gets the [`ResetMode`] currently used: the one given to
+/// [`with_reset_mode`] when called from its closure, or else the one set
+/// with [`set_reset_mode`].
+pub fn reset_mode() -> ResetMode {
+    if let Some(mode) = SCOPED_RESET_MODE.get() {
+        return mode;
+    }
+    match RESET_MODE.load(Ordering::Relaxed) {
+        x if x == ResetMode::Targeted as u8 => ResetMode::Targeted,
+        _ => ResetMode::Full,
+    }
+}
+
+/// Uses a [`ResetMode`] for everything rendered on the current thread while
+/// `f` runs, without changing the one set with [`set_reset_mode`].
+///
+/// ```rust
+/// use colored::{control, Colorize, ResetMode};
+/// # control::set_override(true);
+/// let rendered = control::with_reset_mode(ResetMode::Targeted, || "text".bold().to_string());
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(rendered, "\x1b[1mtext\x1b[22m");
+/// # }
+/// ```
+pub fn with_reset_mode<R>(mode: ResetMode, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous mode, even if `f` panics.
+    struct Restore(Option<ResetMode>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_RESET_MODE.set(self.0);
+        }
+    }
+
+    let _restore = Restore(SCOPED_RESET_MODE.replace(Some(mode)));
+    f()
+}
+
+static PALETTE: RwLock<Palette> = RwLock::new(Palette::XTERM);
+
+thread_local! {
+    static SCOPED_PALETTE: RefCell<Option<Palette>> = const { RefCell::new(None) };
+}
+
+/// Sets the [`Palette`] of the terminal, used to convert the standard colors
+/// to RGB and to find the closest standard color when a color has to be
+/// downgraded.
+///
+/// ```rust
+/// use colored::{control, Palette};
+/// control::set_palette(Palette::SOLARIZED);
+/// assert_eq!(control::palette(), Palette::SOLARIZED);
+/// control::unset_palette();
+/// assert_eq!(control::palette(), Palette::XTERM);
+/// ```
+pub fn set_palette(palette: Palette) {
+    *PALETTE.write().unwrap_or_else(PoisonError::into_inner) = palette;
+}
+
+/// Goes back to the default [`Palette::XTERM`].
+pub fn unset_palette() {
+    set_palette(Palette::XTERM);
+}
+
+/// Gets the [`Palette`] currently in use: the one given to
+/// [`with_palette`] when called from its closure, or else the one set with
+/// [`set_palette`].
+pub fn palette() -> Palette {
+    with_current_palette(Palette::clone)
+}
+
+/// Uses a [`Palette`] for everything rendered on the current thread while
+/// `f` runs, without changing the one set with [`set_palette`].
+///
+/// ```rust
+/// use colored::{control, ColorSupport, Colorize, Palette};
+/// # control::set_override(true);
+/// # control::set_color_support(ColorSupport::Basic);
+/// let dim_blue = "text".truecolor(30, 130, 200);
+/// // Solarized's "blue" is much closer to it than xterm's.
+/// let rendered = control::with_palette(Palette::SOLARIZED, || dim_blue.to_string());
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(rendered, "\x1b[34mtext\x1b[0m");
+/// # }
+/// ```
+pub fn with_palette<R>(palette: Palette, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous palette, even if `f` panics.
+    struct Restore(Option<Palette>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_PALETTE.with(|scoped| *scoped.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = Restore(SCOPED_PALETTE.with(|scoped| scoped.borrow_mut().replace(palette)));
+    f()
+}
+
+/// Runs `f` with the [`Palette`] currently in use, without cloning it.
+pub(crate) fn with_current_palette<R>(f: impl FnOnce(&Palette) -> R) -> R {
+    SCOPED_PALETTE.with(|scoped| match &*scoped.borrow() {
+        Some(palette) => f(palette),
+        None => f(&PALETTE.read().unwrap_or_else(PoisonError::into_inner)),
+    })
+}
+
+/// The theme set with [`set_theme`]: 0 if unset, or else 1 plus the
+/// [`Theme`].
+static THEME: AtomicU8 = AtomicU8::new(0);
+
+thread_local! {
+    static SCOPED_THEME: Cell<Option<Theme>> = const { Cell::new(None) };
+}
+
+/// The theme hinted by the `COLORFGBG` environment variable, used when no
+/// theme is set.
+static DETECTED_THEME: LazyLock<Theme> = LazyLock::new(|| {
+    crate::query::colorfgbg().map_or(Theme::Dark, |(_, background)| {
+        Theme::from_background(background)
+    })
+});
+
+/// Sets the [`Theme`] used to pick the variant of
+/// [`AdaptiveColor`](crate::AdaptiveColor)s, instead of detecting it. This
+/// also makes the rendering of adaptive colors deterministic, e.g. in tests.
+///
+/// ```rust
+/// use colored::{control, AdaptiveColor, Color, Colorize, Theme};
+/// # control::set_override(true);
+/// control::set_theme(Theme::Light);
+/// let text = AdaptiveColor::new(Color::Black, Color::White);
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!("text".color(text).to_string(), "\x1b[30mtext\x1b[0m");
+/// # }
+/// ```
+pub fn set_theme(theme: Theme) {
+    THEME.store(theme as u8 + 1, Ordering::Relaxed);
+}
+
+/// Goes back to guessing the [`Theme`] of the terminal, see [`theme`].
+pub fn unset_theme() {
+    THEME.store(0, Ordering::Relaxed);
+}
+
+/// Gets the [`Theme`] currently in use.
+///
+/// This is the one given to [`with_theme`] when called from its closure, or
+/// else the one set with [`set_theme`] or [`detect_theme`]. Otherwise, it is
+/// guessed from the `COLORFGBG` environment variable, or else it is
+/// [`Theme::Dark`]. The terminal itself is never queried while rendering.
+pub fn theme() -> Theme {
+    if let Some(theme) = SCOPED_THEME.get() {
+        return theme;
+    }
+    match THEME.load(Ordering::Relaxed) {
+        0 => *DETECTED_THEME,
+        x if x == Theme::Light as u8 + 1 => Theme::Light,
+        _ => Theme::Dark,
+    }
+}
+
+/// Uses a [`Theme`] for everything rendered on the current thread while `f`
+/// runs, without changing the one set with [`set_theme`].
+///
+/// ```rust
+/// use colored::{control, Theme};
+/// assert_eq!(control::with_theme(Theme::Light, control::theme), Theme::Light);
+/// ```
+pub fn with_theme<R>(theme: Theme, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous theme, even if `f` panics.
+    struct Restore(Option<Theme>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_THEME.set(self.0);
+        }
+    }
+
+    let _restore = Restore(SCOPED_THEME.replace(Some(theme)));
+    f()
+}
+
+/// Detects the [`Theme`] from the background color of the terminal, queried
+/// with [`query_background`], and uses it from then on as if it was set with
+/// [`set_theme`].
+///
+/// Querying the terminal writes to it and reads its answer from the
+/// standard input, so it is only done when this is called, e.g. when the
+/// program starts, and never while rendering.
+///
+/// ```rust,no_run
+/// use colored::{control, Theme};
+/// let theme = control::detect_theme().unwrap_or(Theme::Dark);
+/// ```
+///
+/// # Errors
+/// Fails like [`query_background`], in which case the theme is left
+/// unchanged.
+pub fn detect_theme() -> Result<Theme, QueryError> {
+    let theme = Theme::from_background(query_background()?);
+    set_theme(theme);
+    Ok(theme)
+}
+
+/// The [`CvdTransform`] applied to rendered colors: 0 if none, or else 1
+/// plus the index of the deficiency, plus 4 for `Daltonize`.
+static CVD_TRANSFORM: LazyLock<AtomicU8> = LazyLock::new(|| {
+    let transform = env::var("COLORED_CVD").ok().and_then(|s| s.parse().ok());
+    AtomicU8::new(encode_cvd_transform(transform))
+});
+
+fn encode_cvd_transform(transform: Option<CvdTransform>) -> u8 {
+    match transform {
+        None => 0,
+        Some(CvdTransform::Simulate(deficiency)) => 1 + deficiency as u8,
+        Some(CvdTransform::Daltonize(deficiency)) => 5 + deficiency as u8,
+    }
+}
+
+/// Sets the [`CvdTransform`] applied to the colors of everything rendered.
+///
+/// This corrects or simulates a color vision deficiency. It defaults to the
+/// value of the `COLORED_CVD` environment variable, e.g. `deuteranopia` or
+/// `simulate-deuteranopia`.
+///
+/// ```rust
+/// use colored::{control, ColorSupport, Colorize, CvdTransform, Deficiency};
+/// # control::set_override(true);
+/// # control::set_color_support(ColorSupport::TrueColor);
+/// control::set_cvd_transform(Some(CvdTransform::Simulate(Deficiency::Achromatopsia)));
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!("red".red().to_string(), "\x1b[38;2;101;101;101mred\x1b[0m");
+/// # }
+/// control::set_cvd_transform(None);
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!("red".red().to_string(), "\x1b[31mred\x1b[0m");
+/// # }
+/// ```
+pub fn set_cvd_transform(transform: Option<CvdTransform>) {
+    CVD_TRANSFORM.store(encode_cvd_transform(transform), Ordering::Relaxed);
+}
+
+/// Gets the [`CvdTransform`] applied to the colors of everything rendered.
+pub fn cvd_transform() -> Option<CvdTransform> {
+    let encoded = usize::from(CVD_TRANSFORM.load(Ordering::Relaxed));
+    let deficiency = *Deficiency::ALL.get(encoded.checked_sub(1)? % 4)?;
+    if encoded > 4 {
+        Some(CvdTransform::Daltonize(deficiency))
+    } else {
+        Some(CvdTransform::Simulate(deficiency))
+    }
+}
+
+/// Whether hyperlinks are written: 0 to detect it from the environment, or
+/// else 1 plus the override.
+static HYPERLINKS: AtomicU8 = AtomicU8::new(0);
+
+static DETECTED_HYPERLINKS: LazyLock<bool> = LazyLock::new(detect_hyperlinks);
+
+thread_local! {
+    static SCOPED_HYPERLINKS: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Checks if the terminal is known not to support OSC 8 hyperlinks, which
+/// it could print as garbage. `FORCE_HYPERLINK=1` or `FORCE_HYPERLINK=0`
+/// overrides the detection.
+fn detect_hyperlinks() -> bool {
+    if let Ok(force) = env::var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    let term = env::var("TERM").unwrap_or_default();
+    let unsupported = term == "dumb"
+        || term == "linux"
+        || term.starts_with("screen")
+        || env::var("TERM_PROGRAM").is_ok_and(|program| program == "Apple_Terminal")
+        || cfg!(windows) && env::var_os("WT_SESSION").is_none();
+    !unsupported
+}
+
+/// Forces [hyperlinks](crate::Colorize::hyperlink) to be written, or not,
+/// instead of detecting if the terminal supports them. Either way, they are
+/// only written when colorizing.
+///
+/// ```rust
+/// use colored::{control, Colorize};
+/// # control::set_override(true);
+/// control::set_hyperlinks(false);
+/// assert_eq!("docs".hyperlink("https://docs.rs").to_string(), "docs");
+/// control::unset_hyperlinks();
+/// ```
+pub fn set_hyperlinks(enabled: bool) {
+    HYPERLINKS.store(u8::from(enabled) + 1, Ordering::Relaxed);
+}
+
+/// Goes back to detecting if the terminal supports hyperlinks.
+pub fn unset_hyperlinks() {
+    HYPERLINKS.store(0, Ordering::Relaxed);
+}
+
+/// Checks if hyperlinks are written: the choice given to [`with_hyperlinks`]
+/// when called from its closure, or else the one set with
+/// [`set_hyperlinks`], or else the detected one.
+pub fn hyperlinks() -> bool {
+    if let Some(enabled) = SCOPED_HYPERLINKS.get() {
+        return enabled;
+    }
+    match HYPERLINKS.load(Ordering::Relaxed) {
+        0 => *DETECTED_HYPERLINKS,
+        x => x == 2,
+    }
+}
+
+/// Writes hyperlinks, or not, for everything rendered on the current thread
+/// while `f` runs, without changing the choice made with [`set_hyperlinks`].
+///
+/// ```rust
+/// use colored::{control, Colorize};
+/// let link = "docs".hyperlink("https://docs.rs");
+/// assert_eq!(control::with_hyperlinks(false, || link.to_string()), "docs");
+/// ```
+pub fn with_hyperlinks<R>(enabled: bool, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous choice, even if `f` panics.
+    struct Restore(Option<bool>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_HYPERLINKS.set(self.0);
+        }
+    }
+
+    let _restore = Restore(SCOPED_HYPERLINKS.replace(Some(enabled)));
+    f()
+}
+
+/// Whether the underline shapes and colors are written: 0 to detect it from
+/// the environment, or else 1 plus the override.
+static EXTENDED_UNDERLINES: AtomicU8 = AtomicU8::new(0);
+
+static DETECTED_EXTENDED_UNDERLINES: LazyLock<bool> =
+    LazyLock::new(|| crate::style::detect_extended_underlines(|name| env::var(name).ok()));
+
+thread_local! {
+    static SCOPED_EXTENDED_UNDERLINES: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Forces the underline styles and colors to be written, or not.
+///
+/// Instead of detecting if the terminal supports them, the
+/// [underline styles](crate::Colorize::underline_style) and
+/// [colors](crate::Colorize::underline_color) are always written, or always
+/// replaced by a plain underline.
+///
+/// ```rust
+/// use colored::{control, Color, Colorize};
+/// # control::set_override(true);
+/// control::set_extended_underlines(false);
+/// let underline = "typo".underline().underline_color(Color::Red);
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(underline.to_string(), "\x1b[4mtypo\x1b[0m");
+/// # }
+/// control::unset_extended_underlines();
+/// ```
+pub fn set_extended_underlines(enabled: bool) {
+    EXTENDED_UNDERLINES.store(u8::from(enabled) + 1, Ordering::Relaxed);
+}
+
+/// Goes back to detecting if the terminal supports the underline styles and
+/// colors.
+pub fn unset_extended_underlines() {
+    EXTENDED_UNDERLINES.store(0, Ordering::Relaxed);
+}
+
+/// Checks if the underline styles and colors are written.
+///
+/// This is the choice given to [`with_extended_underlines`] when called from
+/// its closure, or else the one set with [`set_extended_underlines`], or else
+/// the detected one.
+///
+/// It is detected once, the first time it is needed.
+pub fn extended_underlines() -> bool {
+    if let Some(enabled) = SCOPED_EXTENDED_UNDERLINES.get() {
+        return enabled;
+    }
+    match EXTENDED_UNDERLINES.load(Ordering::Relaxed) {
+        0 => *DETECTED_EXTENDED_UNDERLINES,
+        x => x == 2,
+    }
+}
+
+/// Writes the underline styles and colors, or not, for everything rendered
+/// on the current thread while `f` runs, without changing the choice made
+/// with [`set_extended_underlines`].
+///
+/// ```rust
+/// use colored::{control, Color, Colorize};
+/// # control::set_override(true);
+/// let underline = "typo".underline().underline_color(Color::Red);
+/// let rendered = control::with_extended_underlines(true, || underline.to_string());
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(rendered, "\x1b[4;58;5;1mtypo\x1b[0m");
+/// # }
+/// ```
+pub fn with_extended_underlines<R>(enabled: bool, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous choice, even if `f` panics.
+    struct Restore(Option<bool>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_EXTENDED_UNDERLINES.set(self.0);
+        }
+    }
+
+    let _restore = Restore(SCOPED_EXTENDED_UNDERLINES.replace(Some(enabled)));
+    f()
+}
+
 impl Default for ShouldColorize {
     fn default() -> Self {
         Self {
@@ -0,0 +1,443 @@
+//! Parsing of the CSS color syntax: named colors and functional notations.
+#![allow(clippy::many_single_char_names, clippy::suboptimal_flops)]
+
+use crate::{
+    colorspace::{linear_to_srgb, OkLab},
+    ColorParseErrorKind,
+};
+
+/// The CSS named colors, sorted by name.
+pub const NAMED_COLORS: [(&str, (u8, u8, u8)); 148] = [
+    ("aliceblue", (0xf0, 0xf8, 0xff)),
+    ("antiquewhite", (0xfa, 0xeb, 0xd7)),
+    ("aqua", (0x00, 0xff, 0xff)),
+    ("aquamarine", (0x7f, 0xff, 0xd4)),
+    ("azure", (0xf0, 0xff, 0xff)),
+    ("beige", (0xf5, 0xf5, 0xdc)),
+    ("bisque", (0xff, 0xe4, 0xc4)),
+    ("black", (0x00, 0x00, 0x00)),
+    ("blanchedalmond", (0xff, 0xeb, 0xcd)),
+    ("blue", (0x00, 0x00, 0xff)),
+    ("blueviolet", (0x8a, 0x2b, 0xe2)),
+    ("brown", (0xa5, 0x2a, 0x2a)),
+    ("burlywood", (0xde, 0xb8, 0x87)),
+    ("cadetblue", (0x5f, 0x9e, 0xa0)),
+    ("chartreuse", (0x7f, 0xff, 0x00)),
+    ("chocolate", (0xd2, 0x69, 0x1e)),
+    ("coral", (0xff, 0x7f, 0x50)),
+    ("cornflowerblue", (0x64, 0x95, 0xed)),
+    ("cornsilk", (0xff, 0xf8, 0xdc)),
+    ("crimson", (0xdc, 0x14, 0x3c)),
+    ("cyan", (0x00, 0xff, 0xff)),
+    ("darkblue", (0x00, 0x00, 0x8b)),
+    ("darkcyan", (0x00, 0x8b, 0x8b)),
+    ("darkgoldenrod", (0xb8, 0x86, 0x0b)),
+    ("darkgray", (0xa9, 0xa9, 0xa9)),
+    ("darkgreen", (0x00, 0x64, 0x00)),
+    ("darkgrey", (0xa9, 0xa9, 0xa9)),
+    ("darkkhaki", (0xbd, 0xb7, 0x6b)),
+    ("darkmagenta", (0x8b, 0x00, 0x8b)),
+    ("darkolivegreen", (0x55, 0x6b, 0x2f)),
+    ("darkorange", (0xff, 0x8c, 0x00)),
+    ("darkorchid", (0x99, 0x32, 0xcc)),
+    ("darkred", (0x8b, 0x00, 0x00)),
+    ("darksalmon", (0xe9, 0x96, 0x7a)),
+    ("darkseagreen", (0x8f, 0xbc, 0x8f)),
+    ("darkslateblue", (0x48, 0x3d, 0x8b)),
+    ("darkslategray", (0x2f, 0x4f, 0x4f)),
+    ("darkslategrey", (0x2f, 0x4f, 0x4f)),
+    ("darkturquoise", (0x00, 0xce, 0xd1)),
+    ("darkviolet", (0x94, 0x00, 0xd3)),
+    ("deeppink", (0xff, 0x14, 0x93)),
+    ("deepskyblue", (0x00, 0xbf, 0xff)),
+    ("dimgray", (0x69, 0x69, 0x69)),
+    ("dimgrey", (0x69, 0x69, 0x69)),
+    ("dodgerblue", (0x1e, 0x90, 0xff)),
+    ("firebrick", (0xb2, 0x22, 0x22)),
+    ("floralwhite", (0xff, 0xfa, 0xf0)),
+    ("forestgreen", (0x22, 0x8b, 0x22)),
+    ("fuchsia", (0xff, 0x00, 0xff)),
+    ("gainsboro", (0xdc, 0xdc, 0xdc)),
+    ("ghostwhite", (0xf8, 0xf8, 0xff)),
+    ("gold", (0xff, 0xd7, 0x00)),
+    ("goldenrod", (0xda, 0xa5, 0x20)),
+    ("gray", (0x80, 0x80, 0x80)),
+    ("green", (0x00, 0x80, 0x00)),
+    ("greenyellow", (0xad, 0xff, 0x2f)),
+    ("grey", (0x80, 0x80, 0x80)),
+    ("honeydew", (0xf0, 0xff, 0xf0)),
+    ("hotpink", (0xff, 0x69, 0xb4)),
+    ("indianred", (0xcd, 0x5c, 0x5c)),
+    ("indigo", (0x4b, 0x00, 0x82)),
+    ("ivory", (0xff, 0xff, 0xf0)),
+    ("khaki", (0xf0, 0xe6, 0x8c)),
+    ("lavender", (0xe6, 0xe6, 0xfa)),
+    ("lavenderblush", (0xff, 0xf0, 0xf5)),
+    ("lawngreen", (0x7c, 0xfc, 0x00)),
+    ("lemonchiffon", (0xff, 0xfa, 0xcd)),
+    ("lightblue", (0xad, 0xd8, 0xe6)),
+    ("lightcoral", (0xf0, 0x80, 0x80)),
+    ("lightcyan", (0xe0, 0xff, 0xff)),
+    ("lightgoldenrodyellow", (0xfa, 0xfa, 0xd2)),
+    ("lightgray", (0xd3, 0xd3, 0xd3)),
+    ("lightgreen", (0x90, 0xee, 0x90)),
+    ("lightgrey", (0xd3, 0xd3, 0xd3)),
+    ("lightpink", (0xff, 0xb6, 0xc1)),
+    ("lightsalmon", (0xff, 0xa0, 0x7a)),
+    ("lightseagreen", (0x20, 0xb2, 0xaa)),
+    ("lightskyblue", (0x87, 0xce, 0xfa)),
+    ("lightslategray", (0x77, 0x88, 0x99)),
+    ("lightslategrey", (0x77, 0x88, 0x99)),
+    ("lightsteelblue", (0xb0, 0xc4, 0xde)),
+    ("lightyellow", (0xff, 0xff, 0xe0)),
+    ("lime", (0x00, 0xff, 0x00)),
+    ("limegreen", (0x32, 0xcd, 0x32)),
+    ("linen", (0xfa, 0xf0, 0xe6)),
+    ("magenta", (0xff, 0x00, 0xff)),
+    ("maroon", (0x80, 0x00, 0x00)),
+    ("mediumaquamarine", (0x66, 0xcd, 0xaa)),
+    ("mediumblue", (0x00, 0x00, 0xcd)),
+    ("mediumorchid", (0xba, 0x55, 0xd3)),
+    ("mediumpurple", (0x93, 0x70, 0xdb)),
+    ("mediumseagreen", (0x3c, 0xb3, 0x71)),
+    ("mediumslateblue", (0x7b, 0x68, 0xee)),
+    ("mediumspringgreen", (0x00, 0xfa, 0x9a)),
+    ("mediumturquoise", (0x48, 0xd1, 0xcc)),
+    ("mediumvioletred", (0xc7, 0x15, 0x85)),
+    ("midnightblue", (0x19, 0x19, 0x70)),
+    ("mintcream", (0xf5, 0xff, 0xfa)),
+    ("mistyrose", (0xff, 0xe4, 0xe1)),
+    ("moccasin", (0xff, 0xe4, 0xb5)),
+    ("navajowhite", (0xff, 0xde, 0xad)),
+    ("navy", (0x00, 0x00, 0x80)),
+    ("oldlace", (0xfd, 0xf5, 0xe6)),
+    ("olive", (0x80, 0x80, 0x00)),
+    ("olivedrab", (0x6b, 0x8e, 0x23)),
+    ("orange", (0xff, 0xa5, 0x00)),
+    ("orangered", (0xff, 0x45, 0x00)),
+    ("orchid", (0xda, 0x70, 0xd6)),
+    ("palegoldenrod", (0xee, 0xe8, 0xaa)),
+    ("palegreen", (0x98, 0xfb, 0x98)),
+    ("paleturquoise", (0xaf, 0xee, 0xee)),
+    ("palevioletred", (0xdb, 0x70, 0x93)),
+    ("papayawhip", (0xff, 0xef, 0xd5)),
+    ("peachpuff", (0xff, 0xda, 0xb9)),
+    ("peru", (0xcd, 0x85, 0x3f)),
+    ("pink", (0xff, 0xc0, 0xcb)),
+    ("plum", (0xdd, 0xa0, 0xdd)),
+    ("powderblue", (0xb0, 0xe0, 0xe6)),
+    ("purple", (0x80, 0x00, 0x80)),
+    ("rebeccapurple", (0x66, 0x33, 0x99)),
+    ("red", (0xff, 0x00, 0x00)),
+    ("rosybrown", (0xbc, 0x8f, 0x8f)),
+    ("royalblue", (0x41, 0x69, 0xe1)),
+    ("saddlebrown", (0x8b, 0x45, 0x13)),
+    ("salmon", (0xfa, 0x80, 0x72)),
+    ("sandybrown", (0xf4, 0xa4, 0x60)),
+    ("seagreen", (0x2e, 0x8b, 0x57)),
+    ("seashell", (0xff, 0xf5, 0xee)),
+    ("sienna", (0xa0, 0x52, 0x2d)),
+    ("silver", (0xc0, 0xc0, 0xc0)),
+    ("skyblue", (0x87, 0xce, 0xeb)),
+    ("slateblue", (0x6a, 0x5a, 0xcd)),
+    ("slategray", (0x70, 0x80, 0x90)),
+    ("slategrey", (0x70, 0x80, 0x90)),
+    ("snow", (0xff, 0xfa, 0xfa)),
+    ("springgreen", (0x00, 0xff, 0x7f)),
+    ("steelblue", (0x46, 0x82, 0xb4)),
+    ("tan", (0xd2, 0xb4, 0x8c)),
+    ("teal", (0x00, 0x80, 0x80)),
+    ("thistle", (0xd8, 0xbf, 0xd8)),
+    ("tomato", (0xff, 0x63, 0x47)),
+    ("turquoise", (0x40, 0xe0, 0xd0)),
+    ("violet", (0xee, 0x82, 0xee)),
+    ("wheat", (0xf5, 0xde, 0xb3)),
+    ("white", (0xff, 0xff, 0xff)),
+    ("whitesmoke", (0xf5, 0xf5, 0xf5)),
+    ("yellow", (0xff, 0xff, 0x00)),
+    ("yellowgreen", (0x9a, 0xcd, 0x32)),
+];
+
+/// Looks up a CSS named color.
+pub fn named_color(name: &str) -> Option<(u8, u8, u8)> {
+    NAMED_COLORS
+        .binary_search_by_key(&name, |&(name, _)| name)
+        .ok()
+        .map(|index| NAMED_COLORS[index].1)
+}
+
+/// The names of the supported functional notations.
+pub const FUNCTION_NAMES: [&str; 9] = [
+    "rgb", "rgba", "hsl", "hsla", "hwb", "lab", "lch", "oklab", "oklch",
+];
+
+/// Parses a CSS functional notation such as `rgb(255 128 0)` or
+/// `hsl(30, 100%, 50%)`. The alpha channel is validated but dropped, as
+/// terminals have no notion of transparency.
+pub fn parse_function(s: &str) -> Result<(u8, u8, u8), ColorParseErrorKind> {
+    let (name, args) = s
+        .strip_suffix(')')
+        .and_then(|s| s.split_once('('))
+        .ok_or(ColorParseErrorKind::InvalidSyntax)?;
+    let name = name.trim_end();
+    if !FUNCTION_NAMES.contains(&name) {
+        return Err(ColorParseErrorKind::UnknownName);
+    }
+    let [c1, c2, c3] = components(args)?;
+
+    let rgb = match name {
+        "rgb" | "rgba" => (rgb_channel(c1)?, rgb_channel(c2)?, rgb_channel(c3)?),
+        "hsl" | "hsla" => hsl_to_rgb(hue(c1)?, percentage(c2)?, percentage(c3)?),
+        "hwb" => hwb_to_rgb(hue(c1)?, percentage(c2)?, percentage(c3)?),
+        "lab" => {
+            let l = scaled(c1, 100.0)?;
+            lab_to_rgb(l, scaled(c2, 125.0)?, scaled(c3, 125.0)?)
+        }
+        "lch" => {
+            let (l, c, h) = (scaled(c1, 100.0)?, scaled(c2, 150.0)?, hue(c3)?);
+            let (a, b) = polar_to_cartesian(c, h);
+            lab_to_rgb(l, a, b)
+        }
+        "oklab" => {
+            let l = scaled(c1, 1.0)?;
+            let (a, b) = (scaled(c2, 0.4)?, scaled(c3, 0.4)?);
+            OkLab { l, a, b }.to_rgb()
+        }
+        _ => {
+            let (l, c, h) = (scaled(c1, 1.0)?, scaled(c2, 0.4)?, hue(c3)?);
+            let (a, b) = polar_to_cartesian(c, h);
+            OkLab { l, a, b }.to_rgb()
+        }
+    };
+    Ok(rgb)
+}
+
+/// Splits the arguments of a functional notation into its 3 color
+/// components, supporting both the legacy comma-separated syntax and the
+/// modern space-separated syntax with an optional `/ alpha`.
+fn components(args: &str) -> Result<[&str; 3], ColorParseErrorKind> {
+    let (components, alpha): (Vec<&str>, Option<&str>) = if args.contains(',') {
+        let mut components: Vec<&str> = args.split(',').map(str::trim).collect();
+        let alpha = if components.len() == 4 {
+            components.pop()
+        } else {
+            None
+        };
+        (components, alpha)
+    } else {
+        let (components, alpha) = match args.split_once('/') {
+            Some((components, alpha)) => (components, Some(alpha.trim())),
+            None => (args, None),
+        };
+        (components.split_whitespace().collect(), alpha)
+    };
+
+    if let Some(alpha) = alpha {
+        let alpha = match alpha.strip_suffix('%') {
+            Some(pct) => number(pct)? / 100.0,
+            None => number(alpha)?,
+        };
+        if !(0.0..=1.0).contains(&alpha) {
+            return Err(ColorParseErrorKind::OutOfRange);
+        }
+    }
+
+    components
+        .try_into()
+        .map_err(|_| ColorParseErrorKind::InvalidSyntax)
+}
+
+/// Parses a plain number, where `none` stands for zero.
+fn number(s: &str) -> Result<f32, ColorParseErrorKind> {
+    if s == "none" {
+        return Ok(0.0);
+    }
+    s.parse::<f32>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or(ColorParseErrorKind::InvalidSyntax)
+}
+
+/// Parses a number or a percentage, where 100% is worth `full`.
+fn scaled(s: &str, full: f32) -> Result<f32, ColorParseErrorKind> {
+    match s.strip_suffix('%') {
+        Some(pct) => Ok(number(pct)? / 100.0 * full),
+        None => number(s),
+    }
+}
+
+/// Parses a percentage (or a number between 0 and 100) into a fraction.
+fn percentage(s: &str) -> Result<f32, ColorParseErrorKind> {
+    let pct = number(s.strip_suffix('%').unwrap_or(s))?;
+    if (0.0..=100.0).contains(&pct) {
+        Ok(pct / 100.0)
+    } else {
+        Err(ColorParseErrorKind::OutOfRange)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rgb_channel(s: &str) -> Result<u8, ColorParseErrorKind> {
+    let value = scaled(s, 255.0)?;
+    if (0.0..=255.0).contains(&value) {
+        Ok(value.round() as u8)
+    } else {
+        Err(ColorParseErrorKind::OutOfRange)
+    }
+}
+
+/// Parses an angle into degrees.
+fn hue(s: &str) -> Result<f32, ColorParseErrorKind> {
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f32::consts::PI),
+        ("turn", 360.0),
+    ];
+    for (unit, factor) in units {
+        if let Some(value) = s.strip_suffix(unit) {
+            return Ok(number(value)? * factor);
+        }
+    }
+    number(s)
+}
+
+fn polar_to_cartesian(chroma: f32, hue: f32) -> (f32, f32) {
+    let (sin, cos) = hue.to_radians().sin_cos();
+    (chroma * cos, chroma * sin)
+}
+
+/// Converts a color in the HSL model to RGB, with the saturation and the
+/// lightness as fractions.
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
+    let to_channel = |n: f32| {
+        let k = (n + hue / 30.0).rem_euclid(12.0);
+        let a = saturation * lightness.min(1.0 - lightness);
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    (
+        fraction_to_channel(to_channel(0.0)),
+        fraction_to_channel(to_channel(8.0)),
+        fraction_to_channel(to_channel(4.0)),
+    )
+}
+
+/// Converts a color in the HWB model to RGB, with the whiteness and the
+/// blackness as fractions.
+fn hwb_to_rgb(hue: f32, whiteness: f32, blackness: f32) -> (u8, u8, u8) {
+    if whiteness + blackness >= 1.0 {
+        let gray = fraction_to_channel(whiteness / (whiteness + blackness));
+        return (gray, gray, gray);
+    }
+    let (r, g, b) = hsl_to_rgb(hue, 1.0, 0.5);
+    let to_channel = |c: u8| {
+        fraction_to_channel(f32::from(c) / 255.0 * (1.0 - whiteness - blackness) + whiteness)
+    };
+    (to_channel(r), to_channel(g), to_channel(b))
+}
+
+/// Converts a CIE L*a*b* color, relative to the D50 white point as CSS
+/// specifies, to sRGB.
+fn lab_to_rgb(l: f32, a: f32, b: f32) -> (u8, u8, u8) {
+    const EPSILON: f32 = 216.0 / 24389.0;
+    const KAPPA: f32 = 24389.0 / 27.0;
+    // D50 reference white
+    const WHITE: [f32; 3] = [0.964_22, 1.0, 0.825_21];
+
+    let fy = (l + 16.0) / 116.0;
+    let fx = a / 500.0 + fy;
+    let fz = fy - b / 200.0;
+
+    let x = if fx.powi(3) > EPSILON {
+        fx.powi(3)
+    } else {
+        (116.0 * fx - 16.0) / KAPPA
+    };
+    let y = if l > KAPPA * EPSILON {
+        fy.powi(3)
+    } else {
+        l / KAPPA
+    };
+    let z = if fz.powi(3) > EPSILON {
+        fz.powi(3)
+    } else {
+        (116.0 * fz - 16.0) / KAPPA
+    };
+    let (x, y, z) = (x * WHITE[0], y * WHITE[1], z * WHITE[2]);
+
+    // Bradford chromatic adaptation from D50 to D65
+    let (x, y, z) = (
+        0.955_473_4 * x - 0.023_098_537 * y + 0.063_259_31 * z,
+        -0.028_369_706 * x + 1.009_995_5 * y + 0.021_041_399 * z,
+        0.012_314_002 * x - 0.020_507_697 * y + 1.330_366 * z,
+    );
+
+    let r = 3.240_97 * x - 1.537_383_2 * y - 0.498_610_76 * z;
+    let g = -0.969_243_65 * x + 1.875_967_5 * y + 0.041_555_06 * z;
+    let b = 0.055_630_08 * x - 0.203_976_96 * y + 1.056_971_5 * z;
+
+    (linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn fraction_to_channel(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_colors_are_sorted() {
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn named_color_lookup() {
+        assert_eq!(named_color("rebeccapurple"), Some((0x66, 0x33, 0x99)));
+        assert_eq!(named_color("darkorange"), Some((0xff, 0x8c, 0x00)));
+        assert_eq!(named_color("aliceblue"), Some((0xf0, 0xf8, 0xff)));
+        assert_eq!(named_color("yellowgreen"), Some((0x9a, 0xcd, 0x32)));
+        assert_eq!(named_color("zorglub"), None);
+    }
+
+    macro_rules! make_function_test {
+        ( $( $name:ident: $src:expr => $dst:expr ),* $(,)? ) => {
+            $(
+                #[test]
+                fn $name() {
+                    assert_eq!(parse_function($src), $dst);
+                }
+            )*
+        };
+    }
+
+    make_function_test!(
+        rgb_spaces: "rgb(255 128 0)" => Ok((255, 128, 0)),
+        rgb_commas: "rgb(255, 128, 0)" => Ok((255, 128, 0)),
+        rgb_percentages: "rgb(100% 50% 0%)" => Ok((255, 128, 0)),
+        rgb_alpha: "rgb(255 128 0 / 50%)" => Ok((255, 128, 0)),
+        rgba_commas: "rgba(255, 128, 0, 0.5)" => Ok((255, 128, 0)),
+        rgb_none: "rgb(none 128 0)" => Ok((0, 128, 0)),
+        rgb_out_of_range: "rgb(256 0 0)" => Err(ColorParseErrorKind::OutOfRange),
+        rgb_bad_alpha: "rgb(255 0 0 / 2)" => Err(ColorParseErrorKind::OutOfRange),
+        rgb_too_few: "rgb(255 0)" => Err(ColorParseErrorKind::InvalidSyntax),
+        rgb_too_many: "rgb(255 0 0 0)" => Err(ColorParseErrorKind::InvalidSyntax),
+        rgb_garbage: "rgb(red green blue)" => Err(ColorParseErrorKind::InvalidSyntax),
+        hsl_orange: "hsl(30, 100%, 50%)" => Ok((255, 128, 0)),
+        hsl_spaces: "hsl(30deg 100% 50%)" => Ok((255, 128, 0)),
+        hsl_turn: "hsl(0.5turn 100% 50%)" => Ok((0, 255, 255)),
+        hsl_gray: "hsl(0 0% 50%)" => Ok((128, 128, 128)),
+        hsl_out_of_range: "hsl(0 120% 50%)" => Err(ColorParseErrorKind::OutOfRange),
+        hwb_red: "hwb(0 0% 0%)" => Ok((255, 0, 0)),
+        hwb_gray: "hwb(0 50% 50%)" => Ok((128, 128, 128)),
+        lab_white: "lab(100 0 0)" => Ok((255, 255, 255)),
+        lab_orange: "lab(63.6 53.9 70.4)" => Ok((252, 107, 13)),
+        lch_black: "lch(0 0 0)" => Ok((0, 0, 0)),
+        oklab_white: "oklab(1 0 0)" => Ok((255, 255, 255)),
+        oklch_orange: "oklch(70.49% 0.1867 47.6)" => Ok((249, 115, 22)),
+        unknown_function: "cmyk(0 0 0 0)" => Err(ColorParseErrorKind::UnknownName),
+        unclosed: "rgb(0 0 0" => Err(ColorParseErrorKind::InvalidSyntax),
+    );
+}
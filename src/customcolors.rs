@@ -16,6 +16,128 @@ impl CustomColor {
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
+
+    /// Create a custom color from a hex color: `#rgb` or `#rrggbb`, the `#`
+    /// being optional. The alpha channel of `#rgba` and `#rrggbbaa` is
+    /// dropped.
+    ///
+    /// Unlike parsing a [`Color`](crate::Color), this works in a `const`,
+    /// where an invalid color is a compile error.
+    ///
+    /// ```rust
+    /// # use colored::CustomColor;
+    /// const BRAND: CustomColor = CustomColor::from_hex("#ff8800");
+    /// assert_eq!(BRAND, CustomColor::new(255, 136, 0));
+    /// ```
+    ///
+    /// ```rust,compile_fail
+    /// # use colored::CustomColor;
+    /// const BRAND: CustomColor = CustomColor::from_hex("#ff880");
+    /// ```
+    ///
+    /// ```rust,compile_fail
+    /// # use colored::CustomColor;
+    /// const BRAND: CustomColor = CustomColor::from_hex("#ff8800zz");
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `hex` isn't a valid hex color.
+    #[must_use]
+    pub const fn from_hex(hex: &str) -> Self {
+        let digits = match hex.as_bytes() {
+            [b'#', digits @ ..] | digits => digits,
+        };
+        // the alpha digits are dropped, but must be valid all the same
+        let mut i = 0;
+        while i < digits.len() {
+            hex_digit(digits[i]);
+            i += 1;
+        }
+        match digits {
+            [r, g, b] | [r, g, b, _] => Self::new(
+                hex_digit(*r) * 0x11,
+                hex_digit(*g) * 0x11,
+                hex_digit(*b) * 0x11,
+            ),
+            [r1, r2, g1, g2, b1, b2] | [r1, r2, g1, g2, b1, b2, _, _] => Self::new(
+                hex_digit(*r1) << 4 | hex_digit(*r2),
+                hex_digit(*g1) << 4 | hex_digit(*g2),
+                hex_digit(*b1) << 4 | hex_digit(*b2),
+            ),
+            _ => panic!("a hex color must have 3, 4, 6 or 8 digits"),
+        }
+    }
+}
+
+const fn hex_digit(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        b'A'..=b'F' => digit - b'A' + 10,
+        _ => panic!("invalid hex digit in hex color"),
+    }
+}
+
+/// Creates a [`Color`](crate::Color) from a hex color literal at compile
+/// time, see [`CustomColor::from_hex`]. An invalid color is a compile error.
+///
+/// A [`CustomColor`] is created instead when the literal is preceded by
+/// `CustomColor,`.
+///
+/// ```rust
+/// # use colored::*;
+/// const BRAND: Color = hex!("#ff8800");
+/// assert_eq!(BRAND, Color::TrueColor { r: 255, g: 136, b: 0 });
+/// const ACCENT: CustomColor = hex!(CustomColor, "#08f");
+/// assert_eq!(ACCENT, CustomColor::new(0, 136, 255));
+/// let title = "colored".color(hex!("#f80"));
+/// ```
+///
+/// ```rust,compile_fail
+/// # use colored::*;
+/// const BRAND: Color = hex!("#f80z");
+/// ```
+#[macro_export]
+macro_rules! hex {
+    (CustomColor, $hex:expr $(,)?) => {
+        const { $crate::CustomColor::from_hex($hex) }
+    };
+    (Color, $hex:expr $(,)?) => {
+        $crate::hex!($hex)
+    };
+    ($hex:expr $(,)?) => {
+        const { $crate::Color::from_hex($hex) }
+    };
+}
+
+/// Creates a [`Color::TrueColor`](crate::Color::TrueColor) from its red,
+/// green and blue channels.
+///
+/// A [`CustomColor`] is created instead when the channels are preceded by
+/// `CustomColor,`.
+///
+/// ```rust
+/// # use colored::*;
+/// const BRAND: Color = rgb!(255, 136, 0);
+/// assert_eq!(BRAND, hex!("#ff8800"));
+/// const ACCENT: CustomColor = rgb!(CustomColor, 0, 136, 255);
+/// assert_eq!(ACCENT, hex!(CustomColor, "#0088ff"));
+/// ```
+#[macro_export]
+macro_rules! rgb {
+    (CustomColor, $r:expr, $g:expr, $b:expr $(,)?) => {
+        $crate::CustomColor::new($r, $g, $b)
+    };
+    (Color, $r:expr, $g:expr, $b:expr $(,)?) => {
+        $crate::rgb!($r, $g, $b)
+    };
+    ($r:expr, $g:expr, $b:expr $(,)?) => {
+        $crate::Color::TrueColor {
+            r: $r,
+            g: $g,
+            b: $b,
+        }
+    };
 }
 
 impl From<(u8, u8, u8)> for CustomColor {
@@ -31,7 +153,10 @@ mod tests {
     #[test]
     fn main() {
         let my_color = CustomColor::new(0, 120, 120);
-        insta::assert_snapshot!("Greetings from Ukraine".custom_color(my_color));
+        let greetings = control::with_color_support(ColorSupport::TrueColor, || {
+            "Greetings from Ukraine".custom_color(my_color).to_string()
+        });
+        insta::assert_snapshot!(greetings);
     }
 
     #[test]
@@ -43,4 +168,44 @@ mod tests {
         assert_eq!(cc.g, tuple.1);
         assert_eq!(cc.b, tuple.2);
     }
+
+    #[test]
+    fn from_hex() {
+        const COLORS: [CustomColor; 4] = [
+            CustomColor::from_hex("#ff8800"),
+            CustomColor::from_hex("F80"),
+            CustomColor::from_hex("#Ff8800cc"),
+            CustomColor::from_hex("#f80c"),
+        ];
+        for color in COLORS {
+            assert_eq!(color, CustomColor::new(255, 136, 0));
+        }
+        assert_eq!(
+            CustomColor::from_hex("#0a1b2c"),
+            CustomColor::new(10, 27, 44)
+        );
+        assert_eq!(
+            hex!("#0a1b2c"),
+            Color::TrueColor {
+                r: 10,
+                g: 27,
+                b: 44
+            }
+        );
+        assert_eq!(Color::from(CustomColor::new(10, 27, 44)), rgb!(10, 27, 44));
+        assert_eq!(hex!(Color, "#0a1b2c"), rgb!(Color, 10, 27, 44));
+        assert_eq!(hex!(CustomColor, "#0a1b2c"), rgb!(CustomColor, 10, 27, 44));
+    }
+
+    #[test]
+    #[should_panic = "invalid hex digit"]
+    fn from_hex_invalid_digit() {
+        let _ = CustomColor::from_hex(std::hint::black_box("#ff88gg"));
+    }
+
+    #[test]
+    #[should_panic = "invalid hex digit"]
+    fn from_hex_invalid_alpha_digit() {
+        let _ = CustomColor::from_hex(std::hint::black_box("#ff8800zz"));
+    }
 }
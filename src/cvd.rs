@@ -0,0 +1,292 @@
+//! Simulation and correction of color vision deficiencies.
+#![allow(clippy::suboptimal_flops)]
+
+use crate::{
+    colorspace::{linear_to_srgb, srgb_to_linear},
+    Color,
+};
+use std::str::FromStr;
+
+/// A color vision deficiency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deficiency {
+    /// No red cones: reds look dark and close to greens.
+    Protanopia,
+    /// No green cones, the most common deficiency: reds and greens look
+    /// alike.
+    Deuteranopia,
+    /// No blue cones: blues look like greens and yellows like pinks.
+    Tritanopia,
+    /// No color vision at all, only lightness.
+    Achromatopsia,
+}
+
+/// The simulation matrices of Machado, Oliveira and Fernandes (2009) for a
+/// severity of 1, applied to linear RGB.
+const PROTANOPIA: [[f32; 3]; 3] = [
+    [0.152_286, 1.052_583, -0.204_868],
+    [0.114_503, 0.786_281, 0.099_216],
+    [-0.003_882, -0.048_116, 1.051_998],
+];
+const DEUTERANOPIA: [[f32; 3]; 3] = [
+    [0.367_322, 0.860_646, -0.227_968],
+    [0.280_085, 0.672_501, 0.047_413],
+    [-0.011_820, 0.042_940, 0.968_881],
+];
+const TRITANOPIA: [[f32; 3]; 3] = [
+    [1.255_528, -0.076_749, -0.178_779],
+    [-0.078_411, 0.930_809, 0.147_602],
+    [0.004_733, 0.691_367, 0.303_900],
+];
+
+impl Deficiency {
+    /// All the deficiencies, e.g. to check that a theme stays readable with
+    /// each of them.
+    pub const ALL: [Self; 4] = [
+        Self::Protanopia,
+        Self::Deuteranopia,
+        Self::Tritanopia,
+        Self::Achromatopsia,
+    ];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Protanopia => "protanopia",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Tritanopia => "tritanopia",
+            Self::Achromatopsia => "achromatopsia",
+        }
+    }
+
+    fn simulate_linear(self, [r, g, b]: [f32; 3]) -> [f32; 3] {
+        let matrix = match self {
+            Self::Protanopia => PROTANOPIA,
+            Self::Deuteranopia => DEUTERANOPIA,
+            Self::Tritanopia => TRITANOPIA,
+            Self::Achromatopsia => {
+                let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                return [y, y, y];
+            }
+        };
+        matrix.map(|row| row[0] * r + row[1] * g + row[2] * b)
+    }
+}
+
+/// How colors are transformed for people with a color vision deficiency.
+///
+/// A transform can be applied to everything rendered with
+/// [`control::set_cvd_transform`](crate::control::set_cvd_transform), or
+/// with the `COLORED_CVD` environment variable, e.g. `COLORED_CVD=deuteranopia`
+/// to correct colors or `COLORED_CVD=simulate-deuteranopia` to simulate the
+/// deficiency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CvdTransform {
+    /// Shows colors the way they are seen with the deficiency, see
+    /// [`Color::simulate`].
+    Simulate(Deficiency),
+    /// Corrects colors to be easier to tell apart with the deficiency, see
+    /// [`Color::daltonize`].
+    Daltonize(Deficiency),
+}
+
+impl CvdTransform {
+    /// Applies the transform to a color.
+    #[must_use]
+    pub fn apply(self, color: Color) -> Color {
+        match self {
+            Self::Simulate(deficiency) => color.simulate(deficiency),
+            Self::Daltonize(deficiency) => color.daltonize(deficiency),
+        }
+    }
+}
+
+/// Parses a deficiency name, to correct it, or a deficiency name prefixed
+/// with `simulate-` or `daltonize-`.
+///
+/// ```rust
+/// # use colored::*;
+/// let transform = "simulate-deuteranopia".parse();
+/// assert_eq!(transform, Ok(CvdTransform::Simulate(Deficiency::Deuteranopia)));
+/// ```
+impl FromStr for CvdTransform {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        let (transform, name): (fn(Deficiency) -> Self, _) = match s.split_once('-') {
+            Some(("simulate", name)) => (Self::Simulate, name),
+            Some(("daltonize", name)) => (Self::Daltonize, name),
+            _ => (Self::Daltonize, s.as_str()),
+        };
+        Deficiency::ALL
+            .into_iter()
+            .find(|deficiency| deficiency.name() == name)
+            .map(transform)
+            .ok_or(())
+    }
+}
+
+/// Color vision deficiencies.
+///
+/// These methods return a [`Color::TrueColor`], except for
+/// [`Color::Default`] which is kept unchanged.
+///
+/// ```rust
+/// # use colored::*;
+/// // with deuteranopia, red and green look alike
+/// let red = Color::Red.simulate(Deficiency::Deuteranopia);
+/// let green = Color::Green.simulate(Deficiency::Deuteranopia);
+/// // daltonizing makes them easier to tell apart
+/// let red = Color::Red.daltonize(Deficiency::Deuteranopia);
+/// ```
+impl Color {
+    /// Simulates how the color is seen with a deficiency, using the
+    /// matrices of Machado et al. for dichromacies and the luminance for
+    /// achromatopsia.
+    #[must_use]
+    pub fn simulate(self, deficiency: Deficiency) -> Self {
+        if self == Self::Default {
+            return self;
+        }
+        let rgb = self.to_rgb();
+        let linear = [rgb.r, rgb.g, rgb.b].map(srgb_to_linear);
+        let [r, g, b] = deficiency.simulate_linear(linear).map(linear_to_srgb);
+        Self::TrueColor { r, g, b }
+    }
+
+    /// Corrects the color so that it is easier to tell apart from others
+    /// with a deficiency, by shifting the contrast the deficiency loses to a
+    /// channel that is still seen: the red-green contrast goes to blue for
+    /// protanopia and deuteranopia, and the blue-green contrast to red for
+    /// tritanopia.
+    ///
+    /// Achromatopsia cannot be corrected, the color is then only converted
+    /// to a `TrueColor`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn daltonize(self, deficiency: Deficiency) -> Self {
+        if self == Self::Default {
+            return self;
+        }
+        if deficiency == Deficiency::Achromatopsia {
+            return self.into_truecolor();
+        }
+        let original = self.to_rgb();
+        let simulated = self.simulate(deficiency).to_rgb();
+        let [r, g, b] = [original.r, original.g, original.b].map(f32::from);
+        let [sr, sg, sb] = [simulated.r, simulated.g, simulated.b].map(f32::from);
+        let (er, eg, eb) = (r - sr, g - sg, b - sb);
+        let corrected = if deficiency == Deficiency::Tritanopia {
+            [r + eb - eg, g, b]
+        } else {
+            [r, g, b + er - eg]
+        };
+        let [r, g, b] = corrected.map(|c| c.round().clamp(0.0, 255.0) as u8);
+        Self::TrueColor { r, g, b }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colorspace::OkLab;
+
+    fn distance(c1: Color, c2: Color) -> f32 {
+        let lab = |c: Color| {
+            let rgb = c.to_rgb();
+            OkLab::from_rgb((rgb.r, rgb.g, rgb.b))
+        };
+        lab(c1).distance_squared(lab(c2)).sqrt()
+    }
+
+    #[test]
+    fn grays_are_kept() {
+        for deficiency in Deficiency::ALL {
+            for level in [0, 128, 255] {
+                let gray = Color::TrueColor {
+                    r: level,
+                    g: level,
+                    b: level,
+                };
+                let simulated = deficiency.simulate_linear([srgb_to_linear(level); 3]);
+                let simulated = simulated.map(linear_to_srgb);
+                for c in simulated {
+                    assert!(c.abs_diff(level) <= 1, "{deficiency:?} {gray:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn simulate() {
+        assert_eq!(
+            Color::Red.simulate(Deficiency::Deuteranopia),
+            Color::TrueColor {
+                r: 130,
+                g: 115,
+                b: 0
+            }
+        );
+        let gray = Color::BrightRed
+            .simulate(Deficiency::Achromatopsia)
+            .to_rgb();
+        assert_eq!((gray.r, gray.g), (gray.b, gray.b));
+    }
+
+    #[test]
+    fn red_and_green_look_alike() {
+        let (red, green) = (Color::BrightRed, Color::BrightGreen);
+        let deuteranopia = Deficiency::Deuteranopia;
+        let seen = distance(red.simulate(deuteranopia), green.simulate(deuteranopia));
+        assert!(seen < distance(red, green) / 2.0);
+        let tritanopia = Deficiency::Tritanopia;
+        let seen = distance(red.simulate(tritanopia), green.simulate(tritanopia));
+        assert!(seen > distance(red, green) / 2.0);
+    }
+
+    #[test]
+    fn daltonize() {
+        let pairs = [
+            (Deficiency::Protanopia, Color::Red, Color::Green),
+            (Deficiency::Deuteranopia, Color::Red, Color::Green),
+            (Deficiency::Tritanopia, Color::Blue, Color::Green),
+        ];
+        for (deficiency, c1, c2) in pairs {
+            let seen = |c: Color| c.simulate(deficiency);
+            let before = distance(seen(c1), seen(c2));
+            let corrected = |c: Color| seen(c.daltonize(deficiency));
+            let after = distance(corrected(c1), corrected(c2));
+            assert!(after > before, "{deficiency:?}: {after} <= {before}");
+        }
+        assert_eq!(
+            Color::Red.daltonize(Deficiency::Achromatopsia),
+            Color::TrueColor { r: 205, g: 0, b: 0 }
+        );
+    }
+
+    #[test]
+    fn transforms() {
+        let parse = |s: &str| s.parse::<CvdTransform>();
+        let deuteranopia = Deficiency::Deuteranopia;
+        assert_eq!(
+            parse("deuteranopia"),
+            Ok(CvdTransform::Daltonize(deuteranopia))
+        );
+        assert_eq!(
+            parse("Daltonize-Deuteranopia"),
+            Ok(CvdTransform::Daltonize(deuteranopia))
+        );
+        assert_eq!(
+            parse("simulate-achromatopsia"),
+            Ok(CvdTransform::Simulate(Deficiency::Achromatopsia))
+        );
+        assert_eq!(parse("simulate-"), Err(()));
+        assert_eq!(parse("colorblind"), Err(()));
+
+        let transform = CvdTransform::Simulate(deuteranopia);
+        assert_eq!(
+            transform.apply(Color::Red),
+            Color::Red.simulate(deuteranopia)
+        );
+    }
+}
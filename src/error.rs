@@ -1,5 +1,9 @@
 use super::ColoredString;
-use std::{error::Error, fmt};
+use std::{
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+};
 
 pub struct ColoredStringError(pub ColoredString);
 
@@ -22,3 +26,266 @@ impl fmt::Debug for ColoredStringError {
 }
 
 impl Error for ColoredStringError {}
+
+/// The error returned when a [`Color`](crate::Color) cannot be parsed from a
+/// string.
+///
+/// ```rust
+/// # use colored::*;
+/// let error = "bright-red".parse::<Color>().unwrap_err();
+/// assert_eq!(error.input(), "bright-red");
+/// assert_eq!(error.kind(), ColorParseErrorKind::UnknownName);
+/// assert_eq!(error.suggestion(), Some("bright red"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorParseError {
+    input: String,
+    kind: ColorParseErrorKind,
+    suggestion: Option<&'static str>,
+}
+
+/// The reason why a [`Color`](crate::Color) could not be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ColorParseErrorKind {
+    /// The input is not a known color or function name.
+    UnknownName,
+    /// A hex color doesn't have 3, 4, 6 or 8 digits.
+    InvalidHexLength,
+    /// A hex color contains something else than hexadecimal digits.
+    InvalidHexDigit,
+    /// A component of the color is outside of its allowed range, such as
+    /// `rgb(300 0 0)` or `ansi(256)`.
+    OutOfRange,
+    /// The input is malformed, such as a function with missing arguments.
+    InvalidSyntax,
+}
+
+impl ColorParseError {
+    pub(crate) fn new(
+        input: &str,
+        kind: ColorParseErrorKind,
+        suggestion: Option<&'static str>,
+    ) -> Self {
+        Self {
+            input: input.to_owned(),
+            kind,
+            suggestion,
+        }
+    }
+
+    /// The string that failed to parse.
+    #[must_use]
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Why the string failed to parse.
+    #[must_use]
+    pub fn kind(&self) -> ColorParseErrorKind {
+        self.kind
+    }
+
+    /// A known name close to the input, if there is one.
+    #[must_use]
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.suggestion
+    }
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let input = &self.input;
+        match self.kind {
+            ColorParseErrorKind::UnknownName => write!(f, "unknown color {input:?}")?,
+            ColorParseErrorKind::InvalidHexLength => write!(
+                f,
+                "invalid hex color {input:?}: expected 3, 4, 6 or 8 digits"
+            )?,
+            ColorParseErrorKind::InvalidHexDigit => {
+                write!(f, "invalid hex color {input:?}: invalid hexadecimal digit")?;
+            }
+            ColorParseErrorKind::OutOfRange => {
+                write!(f, "invalid color {input:?}: component out of range")?;
+            }
+            ColorParseErrorKind::InvalidSyntax => write!(f, "invalid color syntax {input:?}")?,
+        }
+        if let Some(suggestion) = self.suggestion {
+            write!(f, ", did you mean {suggestion:?}?")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ColorParseError {}
+
+/// The error returned when a terminal color scheme cannot be loaded into a
+/// [`Palette`](crate::Palette).
+///
+/// ```rust
+/// # use colored::*;
+/// let error = Palette::from_scheme("color0 #000000", SchemeFormat::Kitty).unwrap_err();
+/// assert_eq!(error.kind(), SchemeErrorKind::MissingKey);
+/// assert_eq!(error.key(), Some("color1"));
+/// ```
+#[derive(Debug)]
+pub struct SchemeError {
+    file: Option<PathBuf>,
+    key: Option<String>,
+    value: Option<String>,
+    kind: SchemeErrorKind,
+    source: Option<io::Error>,
+}
+
+/// The reason why a terminal color scheme could not be loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemeErrorKind {
+    /// The file could not be read.
+    Io,
+    /// The format of the file could not be guessed from its extension.
+    UnknownFormat,
+    /// The file is malformed.
+    InvalidSyntax,
+    /// One of the 16 standard colors is missing.
+    MissingKey,
+    /// The value of a color could not be parsed.
+    InvalidColor,
+}
+
+impl SchemeError {
+    pub(crate) fn new(kind: SchemeErrorKind) -> Self {
+        Self {
+            file: None,
+            key: None,
+            value: None,
+            kind,
+            source: None,
+        }
+    }
+
+    pub(crate) fn io(source: io::Error) -> Self {
+        Self {
+            source: Some(source),
+            ..Self::new(SchemeErrorKind::Io)
+        }
+    }
+
+    pub(crate) fn missing_key(key: &str) -> Self {
+        Self::new(SchemeErrorKind::MissingKey).with_key(key)
+    }
+
+    pub(crate) fn invalid_color(key: &str, value: &str) -> Self {
+        Self {
+            value: Some(value.to_owned()),
+            ..Self::new(SchemeErrorKind::InvalidColor).with_key(key)
+        }
+    }
+
+    pub(crate) fn with_key(self, key: &str) -> Self {
+        Self {
+            key: Some(key.to_owned()),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_file(self, file: &Path) -> Self {
+        Self {
+            file: Some(file.to_owned()),
+            ..self
+        }
+    }
+
+    /// The file that failed to load, if the scheme was loaded from a file.
+    #[must_use]
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    /// The key of the scheme that is missing or invalid, if the error is
+    /// about a specific key.
+    #[must_use]
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Why the scheme failed to load.
+    #[must_use]
+    pub fn kind(&self) -> SchemeErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file.display())?;
+        }
+        match self.kind {
+            SchemeErrorKind::Io => write!(f, "cannot read color scheme")?,
+            SchemeErrorKind::UnknownFormat => write!(f, "unknown color scheme format")?,
+            SchemeErrorKind::InvalidSyntax => write!(f, "invalid color scheme syntax")?,
+            SchemeErrorKind::MissingKey => write!(f, "missing color")?,
+            SchemeErrorKind::InvalidColor => match &self.value {
+                Some(value) => write!(f, "invalid color {value:?}")?,
+                None => write!(f, "invalid color")?,
+            },
+        }
+        if let Some(key) = &self.key {
+            write!(f, " for key {key:?}")?;
+        }
+        // the io error is only reported as the source, so that it is not
+        // printed twice by error reporters
+        Ok(())
+    }
+}
+
+impl Error for SchemeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_ref().map(|source| source as _)
+    }
+}
+
+/// The error returned when the colors of the terminal cannot be queried.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum QueryError {
+    /// The standard input or output is not a terminal.
+    NotATerminal,
+    /// The terminal doesn't answer the query, or the platform doesn't
+    /// support querying it.
+    Unsupported,
+    /// The terminal didn't answer in time.
+    Timeout,
+    /// The answer of the terminal could not be parsed.
+    InvalidReply(String),
+    /// Reading from or writing to the terminal failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotATerminal => write!(f, "not a terminal"),
+            Self::Unsupported => write!(f, "the terminal doesn't support color queries"),
+            Self::Timeout => write!(f, "the terminal didn't answer in time"),
+            Self::InvalidReply(reply) => write!(f, "invalid reply from the terminal {reply:?}"),
+            Self::Io(error) => write!(f, "cannot query the terminal: {error}"),
+        }
+    }
+}
+
+impl Error for QueryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for QueryError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
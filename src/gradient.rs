@@ -0,0 +1,166 @@
+use crate::{
+    colorspace::{OkLab, OkLch},
+    Color,
+};
+
+/// Colors interpolated across a text, one color per grapheme cluster.
+///
+/// The stops are evenly spaced from the first to the last grapheme, and the
+/// colors between them are interpolated in the perceptual `OKLab` color
+/// space. The interpolated colors are [`Color::TrueColor`]s, which are
+/// downgraded like any other color on terminals without truecolor support.
+///
+/// ```rust
+/// # use colored::*;
+/// let banner = "Deploy complete".gradient(&[Color::Cyan, Color::Magenta]);
+/// let splash = "colored".rainbow().bold();
+/// let bar = "          ".on_gradient(Gradient::new(vec![Color::Red, Color::Yellow, Color::Green]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Gradient {
+    stops: Vec<Color>,
+}
+
+impl Gradient {
+    /// Creates a gradient going through `stops`, in order.
+    ///
+    /// A gradient without stops leaves the text uncolored, and one with a
+    /// single stop colors it uniformly.
+    #[must_use]
+    pub fn new(stops: Vec<Color>) -> Self {
+        Self { stops }
+    }
+
+    /// The colors of the rainbow, from red to violet, with the same
+    /// lightness.
+    #[must_use]
+    pub fn rainbow() -> Self {
+        let stops = (0..7u8)
+            .map(|i| {
+                let (r, g, b) = OkLch {
+                    l: 0.72,
+                    c: 0.2,
+                    h: f32::from(i).mul_add(45.0, 25.0),
+                }
+                .to_rgb();
+                Color::TrueColor { r, g, b }
+            })
+            .collect();
+        Self { stops }
+    }
+
+    /// The stops of the gradient.
+    #[must_use]
+    pub fn stops(&self) -> &[Color] {
+        &self.stops
+    }
+
+    /// Gets the color at `t`, going from 0 (the first stop) to 1 (the last
+    /// one). Returns `None` if the gradient has no stops.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let gradient = Gradient::from(&[Color::Black, Color::BrightWhite]);
+    /// assert_eq!(gradient.color_at(0.0), Some(Color::Black));
+    /// assert_eq!(gradient.color_at(0.5), Some(Color::TrueColor { r: 99, g: 99, b: 99 }));
+    /// ```
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn color_at(&self, t: f32) -> Option<Color> {
+        let last = self.stops.len().checked_sub(1)?;
+        let position = t.clamp(0.0, 1.0) * last as f32;
+        let index = (position as usize).min(last);
+        let local = position - index as f32;
+        if index == last || local == 0.0 || self.stops[index] == self.stops[index + 1] {
+            return Some(self.stops[index]);
+        }
+
+        let lab = |color: Color| {
+            let rgb = color.to_rgb();
+            OkLab::from_rgb((rgb.r, rgb.g, rgb.b))
+        };
+        let start = lab(self.stops[index]);
+        let end = lab(self.stops[index + 1]);
+        let (r, g, b) = start.mix(end, local).to_rgb();
+        Some(Color::TrueColor { r, g, b })
+    }
+
+    /// Gets the color of the `index`th of `count` graphemes.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn color_of(&self, index: usize, count: usize) -> Option<Color> {
+        let t = if count > 1 {
+            index as f32 / (count - 1) as f32
+        } else {
+            0.0
+        };
+        self.color_at(t)
+    }
+
+    pub(crate) fn map(&self, f: impl Fn(Color) -> Color) -> Self {
+        Self {
+            stops: self.stops.iter().copied().map(f).collect(),
+        }
+    }
+}
+
+impl From<Vec<Color>> for Gradient {
+    fn from(stops: Vec<Color>) -> Self {
+        Self::new(stops)
+    }
+}
+
+impl From<&[Color]> for Gradient {
+    fn from(stops: &[Color]) -> Self {
+        Self::new(stops.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[Color; N]> for Gradient {
+    fn from(stops: &[Color; N]) -> Self {
+        Self::new(stops.to_vec())
+    }
+}
+
+impl<const N: usize> From<[Color; N]> for Gradient {
+    fn from(stops: [Color; N]) -> Self {
+        Self::new(stops.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_are_hit() {
+        let gradient = Gradient::from([Color::Red, Color::Green, Color::Blue]);
+        assert_eq!(gradient.color_of(0, 5), Some(Color::Red));
+        assert_eq!(gradient.color_of(2, 5), Some(Color::Green));
+        assert_eq!(gradient.color_of(4, 5), Some(Color::Blue));
+        assert_eq!(gradient.color_at(2.0), Some(Color::Blue));
+        assert!(matches!(
+            gradient.color_of(1, 5),
+            Some(Color::TrueColor { .. })
+        ));
+    }
+
+    #[test]
+    fn degenerate_gradients() {
+        assert_eq!(Gradient::default().color_at(0.5), None);
+        let single = Gradient::from([Color::Red]);
+        assert_eq!(single.color_at(0.5), Some(Color::Red));
+        assert_eq!(single.color_of(0, 1), Some(Color::Red));
+    }
+
+    #[test]
+    fn rainbow() {
+        let rainbow = Gradient::rainbow();
+        assert_eq!(rainbow.stops().len(), 7);
+        let first = rainbow.stops()[0].to_rgb();
+        assert!(first.r > first.g && first.r > first.b);
+    }
+}
@@ -32,26 +32,91 @@
 #[cfg(test)]
 extern crate rspec;
 
+pub mod ansi;
 mod color;
+mod colorspace;
 pub mod control;
+mod css;
+mod cvd;
 mod error;
+mod gradient;
+mod palette;
+mod query;
+mod render;
+mod scheme;
 mod style;
+mod styled_text;
 
 pub use self::customcolors::CustomColor;
 
 /// Custom colors support.
 pub mod customcolors;
 
+pub use ansi::{display_width, strip_ansi};
 pub use color::*;
-
+pub use cvd::{CvdTransform, Deficiency};
+pub use error::{ColorParseError, ColorParseErrorKind, QueryError, SchemeError, SchemeErrorKind};
+pub use gradient::Gradient;
+pub use palette::Palette;
+pub use render::{Renderer, ResetMode};
+pub use scheme::SchemeFormat;
+
+use render::{Attributes, Nested};
 use std::{
-    borrow::Cow,
     error::Error,
     fmt,
     ops::{Deref, DerefMut},
 };
 
-pub use style::{Style, Styles};
+pub use style::{Style, Styles, UnderlineStyle};
+pub use styled_text::StyledText;
+
+/// The OSC 8 sequence ending a hyperlink.
+const HYPERLINK_END: &str = "\x1B]8;;\x1B\\";
+
+/// Writes what `write` writes, which takes `width` columns, padded to the
+/// width of the formatter with its fill and alignment.
+fn write_padded(
+    f: &mut fmt::Formatter,
+    width: usize,
+    write: impl FnOnce(&mut fmt::Formatter) -> fmt::Result,
+) -> fmt::Result {
+    let padding = f.width().unwrap_or(0).saturating_sub(width);
+    let (before, after) = match f.align() {
+        Some(fmt::Alignment::Right) => (padding, 0),
+        Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+        Some(fmt::Alignment::Left) | None => (0, padding),
+    };
+    let fill = f.fill();
+    for _ in 0..before {
+        write!(f, "{fill}")?;
+    }
+    write(f)?;
+    for _ in 0..after {
+        write!(f, "{fill}")?;
+    }
+    Ok(())
+}
+
+/// Writes the OSC 8 sequence starting a hyperlink to `url`.
+///
+/// The bytes of `url` outside of printable ASCII are percent-encoded, so that
+/// it can't end the sequence and smuggle other ones in, e.g. `\x1B]0;title\x07`
+/// setting the terminal title.
+fn write_hyperlink_start<W: fmt::Write + ?Sized>(out: &mut W, url: &str) -> fmt::Result {
+    out.write_str("\x1B]8;;")?;
+    for part in url.split_inclusive(|c: char| !matches!(c, ' '..='~')) {
+        let (printable, last) = match part.char_indices().next_back() {
+            Some((i, c)) if !matches!(c, ' '..='~') => part.split_at(i),
+            _ => (part, ""),
+        };
+        out.write_str(printable)?;
+        for byte in last.bytes() {
+            write!(out, "%{byte:02X}")?;
+        }
+    }
+    out.write_str("\x1B\\")
+}
 
 /// A string that may have color and/or style applied to it.
 ///
@@ -132,6 +197,21 @@ pub struct ColoredString {
     /// Any special styling to be applied to the text (see Styles for a list of
     /// available options).
     pub style: style::Style,
+    /// A gradient of foreground colors across the text. When set, it is used
+    /// instead of `fgcolor`.
+    pub fg_gradient: Option<Gradient>,
+    /// A gradient of background colors across the text. When set, it is used
+    /// instead of `bgcolor`.
+    pub bg_gradient: Option<Gradient>,
+    /// The color of the underline, if the text is underlined. None means the
+    /// underline has the color of the text.
+    pub underline_color: Option<Color>,
+    /// The shape of the underline, if the text is underlined.
+    pub underline_style: UnderlineStyle,
+    /// The URL the text links to. It is written as an OSC 8 hyperlink when
+    /// colorizing, unless the terminal is known not to support them (see
+    /// [`control::set_hyperlinks`]).
+    pub hyperlink: Option<String>,
 }
 
 /// The trait that enables something to be given color.
@@ -276,6 +356,39 @@ pub trait Colorize {
         self.color(Color::AnsiColor(color.into()))
     }
     fn color<S: Into<Color>>(self, color: S) -> ColoredString;
+    /// Sets the foreground color from a string, failing if it isn't a valid
+    /// color instead of falling back to white like [`Colorize::color`] does.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// assert_eq!("text".try_color("blue"), Ok("text".blue()));
+    /// assert!("text".try_color("bright-red").is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a [`ColorParseError`] if `color` is not a valid color.
+    fn try_color<S: AsRef<str>>(self, color: S) -> Result<ColoredString, ColorParseError>
+    where
+        Self: Sized,
+    {
+        Ok(self.color(color.as_ref().parse::<Color>()?))
+    }
+    /// Colors the text with a [`Gradient`], interpolating the foreground
+    /// color across its grapheme clusters.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let title = "Deploy complete".gradient(&[Color::Cyan, Color::Magenta]);
+    /// ```
+    fn gradient<G: Into<Gradient>>(self, gradient: G) -> ColoredString;
+    /// Colors the text with the colors of the rainbow, see
+    /// [`Gradient::rainbow`].
+    fn rainbow(self) -> ColoredString
+    where
+        Self: Sized,
+    {
+        self.gradient(Gradient::rainbow())
+    }
     // Background Colors
     fn on_black(self) -> ColoredString
     where
@@ -412,6 +525,29 @@ pub trait Colorize {
         self.on_color(Color::AnsiColor(color.into()))
     }
     fn on_color<S: Into<Color>>(self, color: S) -> ColoredString;
+    /// Sets the background color from a string, failing if it isn't a valid
+    /// color instead of falling back to white like [`Colorize::on_color`]
+    /// does.
+    ///
+    /// # Errors
+    /// Returns a [`ColorParseError`] if `color` is not a valid color.
+    fn try_on_color<S: AsRef<str>>(self, color: S) -> Result<ColoredString, ColorParseError>
+    where
+        Self: Sized,
+    {
+        Ok(self.on_color(color.as_ref().parse::<Color>()?))
+    }
+    /// Colors the background with a [`Gradient`], interpolating the
+    /// background color across the grapheme clusters of the text.
+    fn on_gradient<G: Into<Gradient>>(self, gradient: G) -> ColoredString;
+    /// Colors the background with the colors of the rainbow, see
+    /// [`Gradient::rainbow`].
+    fn on_rainbow(self) -> ColoredString
+    where
+        Self: Sized,
+    {
+        self.on_gradient(Gradient::rainbow())
+    }
     // Styles
     fn clear(self) -> ColoredString;
     fn normal(self) -> ColoredString;
@@ -419,12 +555,46 @@ pub trait Colorize {
     fn dimmed(self) -> ColoredString;
     fn italic(self) -> ColoredString;
     fn underline(self) -> ColoredString;
+    /// Underlines the text with a line of the given shape. Terminals that
+    /// don't support it draw a single underline instead.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let typo = "recieve".underline_style(UnderlineStyle::Curly);
+    /// assert!(typo.style.contains(Styles::Underline));
+    /// ```
+    fn underline_style(self, style: UnderlineStyle) -> ColoredString;
+    /// Sets the color of the underline. Terminals that don't support it
+    /// draw the underline with the color of the text.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let typo = "recieve".underline().underline_color(Color::Red);
+    /// ```
+    fn underline_color<S: Into<Color>>(self, color: S) -> ColoredString;
+    /// Makes the text a hyperlink to `url`, which terminals let users click.
+    /// The link is kept by [`Colorize::clear`]. The bytes of `url` outside
+    /// of printable ASCII, such as escape characters, are percent-encoded.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let ticket = "BUG-42".hyperlink("https://example.com/BUG-42").bold();
+    /// assert_eq!(*ticket, *"BUG-42");
+    /// ```
+    fn hyperlink<S: Into<String>>(self, url: S) -> ColoredString;
     fn blink(self) -> ColoredString;
     #[deprecated(since = "1.5.2", note = "Users should use reversed instead")]
     fn reverse(self) -> ColoredString;
     fn reversed(self) -> ColoredString;
     fn hidden(self) -> ColoredString;
     fn strikethrough(self) -> ColoredString;
+    fn overline(self) -> ColoredString;
+    fn double_underline(self) -> ColoredString;
+    fn rapid_blink(self) -> ColoredString;
+    fn framed(self) -> ColoredString;
+    fn encircled(self) -> ColoredString;
+    fn superscript(self) -> ColoredString;
+    fn subscript(self) -> ColoredString;
 }
 
 impl ColoredString {
@@ -477,11 +647,13 @@ impl ColoredString {
     /// will be printed with the default terminal text color.
     pub fn clear_fgcolor(&mut self) {
         self.fgcolor = None;
+        self.fg_gradient = None;
     }
 
     /// Gets rid of this `ColoredString`'s background.
     pub fn clear_bgcolor(&mut self) {
         self.bgcolor = None;
+        self.bg_gradient = None;
     }
 
     /// Clears any special styling and sets it back to the default (plain,
@@ -490,6 +662,57 @@ impl ColoredString {
         self.style = Style::default();
     }
 
+    /// Adjusts the lightness of the foreground color, if needed, for it to
+    /// have a contrast ratio of at least `min_ratio` on the background color.
+    /// See [`Color::ensure_contrast`].
+    ///
+    /// Nothing is changed unless both the foreground and the background
+    /// colors are set, as the terminal's default colors are unknown. Gradients
+    /// are left unchanged.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let label = "warning".bright_yellow().on_bright_white().ensure_contrast(4.5);
+    /// let fg = label.fgcolor.unwrap();
+    /// assert!(fg.contrast_ratio(&Color::BrightWhite) >= 4.5);
+    /// ```
+    #[must_use]
+    pub fn ensure_contrast(mut self, min_ratio: f32) -> Self {
+        if let (Some(fg), Some(bg)) = (self.fgcolor, self.bgcolor) {
+            self.fgcolor = Some(fg.ensure_contrast(bg, min_ratio));
+        }
+        self
+    }
+
+    /// Simulates how the colors are seen with a color vision deficiency.
+    /// See [`Color::simulate`].
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let fail = "FAIL".red().simulate(Deficiency::Deuteranopia);
+    /// let pass = "PASS".green().simulate(Deficiency::Deuteranopia);
+    /// ```
+    #[must_use]
+    pub fn simulate(self, deficiency: Deficiency) -> Self {
+        self.map_colors(|color| color.simulate(deficiency))
+    }
+
+    /// Corrects the colors to be easier to tell apart with a color vision
+    /// deficiency. See [`Color::daltonize`].
+    #[must_use]
+    pub fn daltonize(self, deficiency: Deficiency) -> Self {
+        self.map_colors(|color| color.daltonize(deficiency))
+    }
+
+    fn map_colors(mut self, f: impl Fn(Color) -> Color) -> Self {
+        self.fgcolor = self.fgcolor.map(&f);
+        self.bgcolor = self.bgcolor.map(&f);
+        self.underline_color = self.underline_color.map(&f);
+        self.fg_gradient = self.fg_gradient.map(|gradient| gradient.map(&f));
+        self.bg_gradient = self.bg_gradient.map(|gradient| gradient.map(&f));
+        self
+    }
+
     /// Checks if the colored string has no color or styling.
     ///
     /// ```rust
@@ -501,12 +724,33 @@ impl ColoredString {
     /// ```
     #[must_use]
     pub fn is_plain(&self) -> bool {
-        self.bgcolor.is_none() && self.fgcolor.is_none() && self.style == style::CLEAR
+        self.bgcolor.is_none()
+            && self.fgcolor.is_none()
+            && self.fg_gradient.is_none()
+            && self.bg_gradient.is_none()
+            && self.style == style::CLEAR
+    }
+
+    /// Gets the number of columns the text takes in a terminal, see
+    /// [`display_width`]. It doesn't depend on the colors and style, or on
+    /// whether they are written.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// assert_eq!("日本".red().bold().width(), 4);
+    /// ```
+    #[must_use]
+    pub fn width(&self) -> usize {
+        display_width(&self.input)
+    }
+
+    fn has_gradient(&self) -> bool {
+        self.fg_gradient.is_some() || self.bg_gradient.is_some()
     }
 
     #[cfg(not(feature = "no-color"))]
     fn has_colors() -> bool {
-        control::SHOULD_COLORIZE.should_colorize()
+        control::should_colorize()
     }
 
     #[cfg(feature = "no-color")]
@@ -514,72 +758,110 @@ impl ColoredString {
         false
     }
 
+    #[cfg(test)]
     fn compute_style(&self) -> String {
-        if !Self::has_colors() || self.is_plain() {
-            return String::new();
+        let mut style = String::new();
+        if Self::has_colors() && !self.is_plain() {
+            Attributes::new(self, self.fgcolor, self.bgcolor)
+                .write_from(&Attributes::default(), &mut style)
+                .unwrap();
         }
+        style
+    }
 
-        let mut res = String::from("\x1B[");
-        let mut has_wrote = if self.style == style::CLEAR {
-            false
-        } else {
-            res.push_str(&self.style.to_str());
-            true
+    /// The colors of the `index`th of `count` grapheme clusters, taking the
+    /// gradients into account.
+    fn colors_of(&self, index: usize, count: usize) -> (Option<Color>, Option<Color>) {
+        let color_of = |gradient: &Option<Gradient>, color: Option<Color>| {
+            gradient
+                .as_ref()
+                .map_or(color, |gradient| gradient.color_of(index, count))
         };
-
-        if let Some(ref bgcolor) = self.bgcolor {
-            if has_wrote {
-                res.push(';');
-            }
-
-            res.push_str(&bgcolor.to_bg_str());
-            has_wrote = true;
-        }
-
-        if let Some(ref fgcolor) = self.fgcolor {
-            if has_wrote {
-                res.push(';');
-            }
-
-            res.push_str(&fgcolor.to_fg_str());
-        }
-
-        res.push('m');
-        res
+        (
+            color_of(&self.fg_gradient, self.fgcolor),
+            color_of(&self.bg_gradient, self.bgcolor),
+        )
+    }
+
+    /// Writes the text with the colors of the gradients, see
+    /// [`render::write_gradient`]. It is padded by its width in columns, and
+    /// truncated to the precision of the formatter in chars.
+    fn fmt_gradient(&self, f: &mut fmt::Formatter, hyperlink: Option<&str>) -> fmt::Result {
+        let text = match f.precision() {
+            Some(precision) => self
+                .input
+                .char_indices()
+                .nth(precision)
+                .map_or(self.input.as_str(), |(end, _)| &self.input[..end]),
+            None => &self.input,
+        };
+        write_padded(f, display_width(text), |f| {
+            render::write_gradient(self, text, hyperlink, f)
+        })
     }
 
-    fn escape_inner_reset_sequences(&self) -> Cow<str> {
-        if !Self::has_colors() || self.is_plain() {
-            return self.input.as_str().into();
+    /// The URL of the hyperlink, if there is one and it is written.
+    fn hyperlink_url(&self) -> Option<&str> {
+        self.hyperlink
+            .as_deref()
+            .filter(|_| Self::has_colors() && control::hyperlinks())
+    }
+
+    #[cfg(test)]
+    fn escape_inner_reset_sequences(&self) -> String {
+        let attributes = (Self::has_colors() && !self.is_plain())
+            .then(|| Attributes::new(self, self.fgcolor, self.bgcolor));
+        let mut escaped = String::new();
+        self.write_escaped(&mut escaped, attributes.as_ref(), self.hyperlink_url())
+            .unwrap();
+        escaped
+    }
+
+    /// Writes the text, padded by the formatter. Its escape sequences are
+    /// written as they are, except that they are escaped when it is written
+    /// with `attributes` or a `hyperlink`, see
+    /// [`write_escaped`](Self::write_escaped).
+    fn fmt_input(
+        &self,
+        f: &mut fmt::Formatter,
+        attributes: Option<&Attributes>,
+        hyperlink: Option<&str>,
+    ) -> fmt::Result {
+        if (attributes.is_none() && hyperlink.is_none()) || !self.input.contains('\x1B') {
+            return <str as fmt::Display>::fmt(&self.input, f);
         }
-
-        // TODO: BoyScoutRule
-        let reset = "\x1B[0m";
-        let style = self.compute_style();
-        let matches: Vec<usize> = self
-            .input
-            .match_indices(reset)
-            .map(|(idx, _)| idx)
-            .collect();
-        if matches.is_empty() {
-            return self.input.as_str().into();
+        if f.width().is_none() && f.precision().is_none() {
+            return self.write_escaped(f, attributes, hyperlink);
         }
-
-        let mut input = self.input.clone();
-        input.reserve(matches.len() * style.len());
-
-        for (idx_in_matches, offset) in matches.into_iter().enumerate() {
-            // shift the offset to the end of the reset sequence and take in account
-            // the number of matches we have escaped (which shift the index to insert)
-            let mut offset = offset + reset.len() + idx_in_matches * style.len();
-
-            for cchar in style.chars() {
-                input.insert(offset, cchar);
-                offset += 1;
+        let mut escaped = String::with_capacity(self.input.len());
+        self.write_escaped(&mut escaped, attributes, hyperlink)?;
+        <str as fmt::Display>::fmt(&escaped, f)
+    }
+
+    /// Writes the text in a single pass, setting the `attributes` back after
+    /// each SGR sequence turning some of them off, e.g. the reset ending a
+    /// nested colored string, whatever its form (`ESC[0m`, `ESC[m`,
+    /// `ESC[39m`, ...). A hyperlink ending in the text ends the `hyperlink`
+    /// too, so it is restarted.
+    fn write_escaped<W: fmt::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        attributes: Option<&Attributes>,
+        hyperlink: Option<&str>,
+    ) -> fmt::Result {
+        let mut nested = Nested::default();
+        let mut start = 0;
+        for range in ansi::sequences(&self.input) {
+            let sequence = &self.input[range.clone()];
+            out.write_str(&self.input[start..range.end])?;
+            if let (Some(attributes), Some(params)) = (attributes, ansi::sgr_params(sequence)) {
+                attributes.write_restored_after(params, &mut nested, out)?;
+            } else if let Some(url) = hyperlink.filter(|_| sequence == HYPERLINK_END) {
+                write_hyperlink_start(out, url)?;
             }
+            start = range.end;
         }
-
-        input.into()
+        out.write_str(&self.input[start..])
     }
 }
 
@@ -617,16 +899,27 @@ impl<'a> From<&'a str> for ColoredString {
 impl Colorize for ColoredString {
     fn color<S: Into<Color>>(mut self, color: S) -> ColoredString {
         self.fgcolor = Some(color.into());
+        self.fg_gradient = None;
         self
     }
     fn on_color<S: Into<Color>>(mut self, color: S) -> ColoredString {
         self.bgcolor = Some(color.into());
+        self.bg_gradient = None;
+        self
+    }
+    fn gradient<G: Into<Gradient>>(mut self, gradient: G) -> ColoredString {
+        self.fg_gradient = Some(gradient.into());
+        self
+    }
+    fn on_gradient<G: Into<Gradient>>(mut self, gradient: G) -> ColoredString {
+        self.bg_gradient = Some(gradient.into());
         self
     }
 
     fn clear(self) -> ColoredString {
         Self {
             input: self.input,
+            hyperlink: self.hyperlink,
             ..Self::default()
         }
     }
@@ -649,6 +942,19 @@ impl Colorize for ColoredString {
         self.style.add(style::Styles::Underline);
         self
     }
+    fn underline_style(mut self, style: UnderlineStyle) -> ColoredString {
+        self.style.add(style::Styles::Underline);
+        self.underline_style = style;
+        self
+    }
+    fn underline_color<S: Into<Color>>(mut self, color: S) -> ColoredString {
+        self.underline_color = Some(color.into());
+        self
+    }
+    fn hyperlink<S: Into<String>>(mut self, url: S) -> ColoredString {
+        self.hyperlink = Some(url.into());
+        self
+    }
     fn blink(mut self) -> ColoredString {
         self.style.add(style::Styles::Blink);
         self
@@ -668,6 +974,34 @@ impl Colorize for ColoredString {
         self.style.add(style::Styles::Strikethrough);
         self
     }
+    fn overline(mut self) -> ColoredString {
+        self.style.add(style::Styles::Overline);
+        self
+    }
+    fn double_underline(mut self) -> ColoredString {
+        self.style.add(style::Styles::DoubleUnderline);
+        self
+    }
+    fn rapid_blink(mut self) -> ColoredString {
+        self.style.add(style::Styles::RapidBlink);
+        self
+    }
+    fn framed(mut self) -> ColoredString {
+        self.style.add(style::Styles::Framed);
+        self
+    }
+    fn encircled(mut self) -> ColoredString {
+        self.style.add(style::Styles::Encircled);
+        self
+    }
+    fn superscript(mut self) -> ColoredString {
+        self.style.add(style::Styles::Superscript);
+        self
+    }
+    fn subscript(mut self) -> ColoredString {
+        self.style.add(style::Styles::Subscript);
+        self
+    }
 }
 
 impl Colorize for &str {
@@ -687,6 +1021,14 @@ impl Colorize for &str {
         }
     }
 
+    fn gradient<G: Into<Gradient>>(self, gradient: G) -> ColoredString {
+        ColoredString::from(self).gradient(gradient)
+    }
+
+    fn on_gradient<G: Into<Gradient>>(self, gradient: G) -> ColoredString {
+        ColoredString::from(self).on_gradient(gradient)
+    }
+
     fn clear(self) -> ColoredString {
         ColoredString {
             input: String::from(self),
@@ -709,6 +1051,15 @@ impl Colorize for &str {
     fn underline(self) -> ColoredString {
         ColoredString::from(self).underline()
     }
+    fn underline_style(self, style: UnderlineStyle) -> ColoredString {
+        ColoredString::from(self).underline_style(style)
+    }
+    fn underline_color<S: Into<Color>>(self, color: S) -> ColoredString {
+        ColoredString::from(self).underline_color(color)
+    }
+    fn hyperlink<S: Into<String>>(self, url: S) -> ColoredString {
+        ColoredString::from(self).hyperlink(url)
+    }
     fn blink(self) -> ColoredString {
         ColoredString::from(self).blink()
     }
@@ -724,20 +1075,55 @@ impl Colorize for &str {
     fn strikethrough(self) -> ColoredString {
         ColoredString::from(self).strikethrough()
     }
+    fn overline(self) -> ColoredString {
+        ColoredString::from(self).overline()
+    }
+    fn double_underline(self) -> ColoredString {
+        ColoredString::from(self).double_underline()
+    }
+    fn rapid_blink(self) -> ColoredString {
+        ColoredString::from(self).rapid_blink()
+    }
+    fn framed(self) -> ColoredString {
+        ColoredString::from(self).framed()
+    }
+    fn encircled(self) -> ColoredString {
+        ColoredString::from(self).encircled()
+    }
+    fn superscript(self) -> ColoredString {
+        ColoredString::from(self).superscript()
+    }
+    fn subscript(self) -> ColoredString {
+        ColoredString::from(self).subscript()
+    }
 }
 
 impl fmt::Display for ColoredString {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if !Self::has_colors() || self.is_plain() {
-            return <String as fmt::Display>::fmt(&self.input, f);
+        let hyperlink = self.hyperlink_url();
+        if !Self::has_colors() || (self.is_plain() && hyperlink.is_none()) {
+            return <str as fmt::Display>::fmt(&self.input, f);
         }
 
-        // XXX: see tests. Useful when nesting colored strings
-        let escaped_input = self.escape_inner_reset_sequences();
+        if let Some(url) = hyperlink {
+            write_hyperlink_start(f, url)?;
+        }
 
-        f.write_str(&self.compute_style())?;
-        escaped_input.fmt(f)?;
-        f.write_str("\x1B[0m")?;
+        if self.has_gradient() {
+            self.fmt_gradient(f, hyperlink)?;
+        } else if self.is_plain() {
+            // XXX: see tests. Useful when nesting colored strings
+            self.fmt_input(f, None, hyperlink)?;
+        } else {
+            let attributes = Attributes::new(self, self.fgcolor, self.bgcolor);
+            attributes.write_from(&Attributes::default(), f)?;
+            self.fmt_input(f, Some(&attributes), hyperlink)?;
+            attributes.write_reset(f)?;
+        }
+
+        if hyperlink.is_some() {
+            f.write_str(HYPERLINK_END)?;
+        }
         Ok(())
     }
 }
@@ -800,11 +1186,20 @@ mod tests {
         writeln!(&mut buf, "{}", toto.cyan())?;
         writeln!(&mut buf, "{}", toto.white())?;
         writeln!(&mut buf, "{}", toto.white().red().blue().green())?;
-        writeln!(&mut buf, "{}", toto.truecolor(255, 0, 0))?;
-        writeln!(&mut buf, "{}", toto.truecolor(255, 255, 0))?;
-        writeln!(&mut buf, "{}", toto.on_truecolor(0, 80, 80))?;
-        writeln!(&mut buf, "{}", toto.custom_color((255, 255, 0)))?;
-        writeln!(&mut buf, "{}", toto.on_custom_color((0, 80, 80)))?;
+        // the truecolors are written as they are, whatever the terminal
+        let truecolors = control::with_color_support(ColorSupport::TrueColor, || {
+            [
+                toto.truecolor(255, 0, 0),
+                toto.truecolor(255, 255, 0),
+                toto.on_truecolor(0, 80, 80),
+                toto.custom_color((255, 255, 0)),
+                toto.on_custom_color((0, 80, 80)),
+            ]
+            .map(|string| string.to_string())
+        });
+        for line in truecolors {
+            writeln!(&mut buf, "{line}")?;
+        }
         #[cfg(feature = "no-color")]
         insta::assert_snapshot!("it_works_no_color", buf);
         #[cfg(not(feature = "no-color"))]
@@ -895,6 +1290,25 @@ mod tests {
         );
     }
 
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn compute_style_extended_styles() {
+        let extended = "\x1B[1;53;21;6;51;52;73m";
+
+        assert_eq!(
+            extended,
+            "".superscript()
+                .encircled()
+                .framed()
+                .rapid_blink()
+                .double_underline()
+                .overline()
+                .bold()
+                .compute_style()
+        );
+        assert_eq!("\x1B[74m", "".subscript().compute_style());
+    }
+
     #[test]
     fn escape_reset_sequence_spec_should_do_nothing_on_empty_strings() {
         let style = ColoredString::default();
@@ -932,6 +1346,147 @@ mod tests {
         assert_eq!(expected, output);
     }
 
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn escape_reset_sequence_spec_should_handle_all_reset_forms() {
+        let blue = "\x1B[34m";
+        let cases = [
+            ("\x1B[31mx\x1B[m", format!("\x1B[31mx\x1B[m{blue}")),
+            ("\x1B[31mx\x1B[00m", format!("\x1B[31mx\x1B[00m{blue}")),
+            ("\x1B[31mx\x1B[39m", format!("\x1B[31mx\x1B[39m{blue}")),
+            (
+                "\x1B[1;31mx\x1B[22;39m",
+                format!("\x1B[1;31mx\x1B[22;39m{blue}"),
+            ),
+            ("\x1B[1mx\x1B[22m", "\x1B[1mx\x1B[22m".to_owned()),
+            ("\x1B[0;31mx", "\x1B[0;31mx".to_owned()),
+            ("\x1B[2Kx\x1B[39m", "\x1B[2Kx\x1B[39m".to_owned()),
+        ];
+        for (inner, expected) in cases {
+            let output = format!("<{inner}>").blue();
+            assert_eq!(
+                output.escape_inner_reset_sequences(),
+                format!("<{expected}>"),
+                "{inner:?}"
+            );
+        }
+
+        let bold = format!("<{}>", "x".red()).bold();
+        assert_eq!(
+            bold.escape_inner_reset_sequences(),
+            "<\x1B[31mx\x1B[0m\x1B[1m>"
+        );
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn targeted_reset_mode() {
+        control::with_reset_mode(ResetMode::Targeted, || {
+            let inner = "x".red().italic();
+            assert_eq!(inner.to_string(), "\x1B[3;31mx\x1B[23;39m");
+            let outer = format!("<{inner}>").bold().blue();
+            assert_eq!(
+                outer.to_string(),
+                "\x1B[1;34m<\x1B[3;31mx\x1B[23;39m\x1B[34m>\x1B[22;39m"
+            );
+            assert_eq!(
+                "ab".gradient([Color::Red, Color::Blue]).to_string(),
+                "\x1B[31ma\x1B[34mb\x1B[39m"
+            );
+        });
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn default_color_resets_the_enclosing_color() {
+        let plain = "plain".color(Color::Default).on_color(Color::Default);
+        assert_eq!(plain.compute_style(), "\x1B[49;39m");
+
+        let input = format!("start {} end", "plain".color(Color::Default));
+        let output = input.blue().on_red().to_string();
+        let parent = "\x1B[41;34m";
+        let reset = "\x1B[0m";
+        let expected = format!("{parent}start \x1B[39mplain{reset}{parent} end{reset}");
+        assert_eq!(expected, output);
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn nested_strings_are_padded_once_escaped() {
+        let nested = format!("a{}b", "c".blue()).red();
+        let escaped = "a\x1B[34mc\x1B[0m\x1B[31mb";
+        assert_eq!(nested.to_string(), format!("\x1B[31m{escaped}\x1B[0m"));
+        assert_eq!(
+            format!("{nested:>20}|"),
+            format!("\x1B[31m   {escaped}\x1B[0m|")
+        );
+
+        let many = "x".green().to_string().repeat(1000).red().to_string();
+        assert_eq!(many.matches("\x1B[0m\x1B[31m").count(), 1000);
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn hyperlinks() {
+        let start = |url: &str| format!("\x1B]8;;{url}\x1B\\");
+        let end = HYPERLINK_END;
+        let docs = start("https://docs.rs");
+        let crates = start("https://crates.io");
+
+        let outer = control::with_hyperlinks(true, || {
+            let link = "docs".hyperlink("https://docs.rs");
+            assert_eq!(link.to_string(), format!("{docs}docs{end}"));
+            let link = link.red().clear();
+            assert_eq!(link.to_string(), format!("{docs}docs{end}"));
+            let link = link.bold();
+            assert_eq!(link.to_string(), format!("{docs}\x1B[1mdocs\x1B[0m{end}"));
+
+            // the outer link is restarted after the inner one
+            let inner = "crate".hyperlink("https://crates.io");
+            let outer = format!("see {inner} on docs").hyperlink("https://docs.rs");
+            assert_eq!(
+                outer.to_string(),
+                format!("{docs}see {crates}crate{end}{docs} on docs{end}")
+            );
+            outer
+        });
+
+        let rendered = control::with_hyperlinks(false, || outer.to_string());
+        assert_eq!(rendered, format!("see {crates}crate{end} on docs"));
+    }
+
+    #[test]
+    fn hyperlink_urls_are_escaped() {
+        let start = |url: &str| {
+            let mut out = String::new();
+            write_hyperlink_start(&mut out, url).unwrap();
+            out
+        };
+        assert_eq!(
+            start("https://example.com/a b?q=1;2#~"),
+            "\x1B]8;;https://example.com/a b?q=1;2#~\x1B\\"
+        );
+        // the url can't end the sequence to write another one
+        assert_eq!(
+            start("https://x\x1b]0;pwned\x07"),
+            "\x1B]8;;https://x%1B]0;pwned%07\x1B\\"
+        );
+        assert_eq!(start("https://x/é\n"), "\x1B]8;;https://x/%C3%A9%0A\x1B\\");
+    }
+
+    #[test]
+    fn width() {
+        let rainbow = "🦀 日本".rainbow().bold().hyperlink("https://docs.rs");
+        let nested = format!("{} 日本", "🦀".on_blue()).red();
+        for colorize in [true, false] {
+            for colored in [&rainbow, &nested] {
+                assert_eq!(colored.width(), 7);
+                let rendered = control::with_override(colorize, || colored.to_string());
+                assert_eq!(display_width(&rendered), 7);
+            }
+        }
+    }
+
     #[cfg_attr(feature = "no-color", ignore)]
     #[test]
     fn escape_reset_sequence_spec_should_replace_multiple_inner_reset_sequences_with_current_style()
@@ -968,11 +1523,119 @@ mod tests {
         assert_eq!("blue".bright_blue(), "blue".color("bright blue"));
     }
 
+    #[test]
+    fn try_color_fn() {
+        assert_eq!("blue".try_color("blue"), Ok("blue".blue()));
+        assert_eq!("blue".try_on_color("blue"), Ok("blue".on_blue()));
+
+        let error = "blue".try_color("bleu").unwrap_err();
+        assert_eq!(error.kind(), ColorParseErrorKind::UnknownName);
+        assert_eq!(error.suggestion(), Some("blue"));
+        assert!("blue".to_string().try_on_color("#12").is_err());
+        assert!("blue".red().try_color("ansi(256)").is_err());
+    }
+
     #[test]
     fn on_bright_color_fn() {
         assert_eq!("blue".on_bright_blue(), "blue".on_color("bright blue"));
     }
 
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn adaptive_colors() {
+        let dim = AdaptiveColor::new(Color::BrightBlack, Color::White);
+        let text = "text"
+            .color(dim)
+            .on_color(AdaptiveColor::new(Color::White, Color::Black));
+
+        let light = control::with_theme(Theme::Light, || text.compute_style());
+        assert_eq!(light, "\x1B[47;90m");
+        let dark = control::with_theme(Theme::Dark, || text.compute_style());
+        assert_eq!(dark, "\x1B[40;37m");
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn gradients() {
+        // "e" and its combining accent are a single grapheme cluster
+        let text = "e\u{301}x".gradient([Color::Red, Color::Blue]).bold();
+        assert_eq!(text.to_string(), "\x1B[1;31me\u{301}\x1B[1;34mx\x1B[0m");
+
+        // the sequence isn't repeated for graphemes of the same color
+        let text = "abc".on_gradient([Color::Green, Color::Green]).red();
+        assert_eq!(text.to_string(), "\x1B[42;31mabc\x1B[0m");
+
+        let text = "abc".gradient(Gradient::default());
+        assert_eq!(text.to_string(), "abc");
+        assert_eq!("abc".rainbow().red(), "abc".red());
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn gradients_are_padded() {
+        let text = "abc".gradient([Color::Red, Color::Red]);
+        assert_eq!(format!("[{text:>8}]"), "[     \x1B[31mabc\x1B[0m]");
+        assert_eq!(format!("[{text:-<5}]"), "[\x1B[31mabc\x1B[0m--]");
+        assert_eq!(format!("[{text:.2}]"), "[\x1B[31mab\x1B[0m]");
+        let wide = "日本".gradient([Color::Red, Color::Red]);
+        assert_eq!(format!("[{wide:^6}]"), "[ \x1B[31m日本\x1B[0m ]");
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn gradients_over_nested_strings() {
+        let red = [Color::Red, Color::Red];
+        let bold = format!("a{}b", "c".bold()).gradient(red);
+        assert_eq!(bold.to_string(), "\x1B[31ma\x1B[1mc\x1B[0m\x1B[31mb\x1B[0m");
+        // the color of the nested string is kept until it ends
+        let blue = format!("a{}b", "c".blue()).gradient(red);
+        assert_eq!(
+            blue.to_string(),
+            "\x1B[31ma\x1B[34mc\x1B[0m\x1B[31mb\x1B[0m"
+        );
+        // the sequences are not split
+        let truecolor = format!("a{}", "b".truecolor(1, 2, 3)).gradient([Color::Red, Color::Blue]);
+        assert_eq!(strip_ansi(&truecolor.to_string()), "ab");
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn extended_underlines() {
+        let squiggle = "typo"
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Color::TrueColor { r: 255, g: 0, b: 0 })
+            .bold();
+
+        // other terminals may misread the sequences, so only a plain
+        // underline is used unless the terminal is known to support them
+        let plain = control::with_extended_underlines(false, || squiggle.compute_style());
+        assert_eq!(plain, "\x1B[1;4m");
+
+        control::with_extended_underlines(true, || {
+            let truecolor =
+                control::with_color_support(ColorSupport::TrueColor, || squiggle.compute_style());
+            assert_eq!(truecolor, "\x1B[1;4:3;58;2;255;0;0m");
+            let underline = "text".underline().underline_color(Color::Red).green();
+            assert_eq!(underline.compute_style(), "\x1B[4;32;58;5;1m");
+        });
+    }
+
+    #[test]
+    fn ensure_contrast() {
+        let readable = "a".black().on_white().ensure_contrast(4.5);
+        assert_eq!(readable, "a".black().on_white());
+
+        let fixed = "a".bright_yellow().on_bright_white().ensure_contrast(4.5);
+        assert_ne!(fixed.fgcolor, Some(Color::BrightYellow));
+        assert!(fixed.fgcolor.unwrap().contrast_ratio(&Color::BrightWhite) >= 4.5);
+
+        // the terminal's default colors are unknown
+        let foreground_only = "a".bright_yellow().ensure_contrast(4.5);
+        assert_eq!(foreground_only.fgcolor, Some(Color::BrightYellow));
+        let background_only = "a".on_bright_white().ensure_contrast(4.5);
+        assert_eq!(background_only.fgcolor, None);
+    }
+
     #[test]
     fn exposing_tests() {
         #![allow(deprecated)]
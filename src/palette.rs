@@ -0,0 +1,237 @@
+use crate::CustomColor;
+
+/// The levels of the xterm 6x6x6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The RGB values the terminal uses for the indices of the 256 color
+/// palette.
+///
+/// The values of the 16 standard colors depend on the terminal and on its
+/// theme, so they are used whenever a [`Color`](crate::Color) has to be
+/// converted to RGB, and to find the closest standard color when a
+/// `TrueColor` has to be downgraded. The default palette is
+/// [`Palette::XTERM`]; another one can be set with
+/// [`control::set_palette`](crate::control::set_palette), or for a single
+/// render with [`control::with_palette`](crate::control::with_palette).
+///
+/// ```rust
+/// use colored::{CustomColor, Palette};
+/// assert_eq!(Palette::SOLARIZED.get(4), CustomColor::new(38, 139, 210));
+/// // The 6x6x6 color cube and the grayscale ramp are filled in.
+/// assert_eq!(Palette::SOLARIZED.get(208), CustomColor::new(255, 135, 0));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: [CustomColor; 256],
+}
+
+impl Palette {
+    /// The default colors of xterm.
+    pub const XTERM: Self = Self::new([
+        CustomColor::new(0, 0, 0),
+        CustomColor::new(205, 0, 0),
+        CustomColor::new(0, 205, 0),
+        CustomColor::new(205, 205, 0),
+        CustomColor::new(0, 0, 238),
+        CustomColor::new(205, 0, 205),
+        CustomColor::new(0, 205, 205),
+        CustomColor::new(229, 229, 229),
+        CustomColor::new(127, 127, 127),
+        CustomColor::new(255, 0, 0),
+        CustomColor::new(0, 255, 0),
+        CustomColor::new(255, 255, 0),
+        CustomColor::new(92, 92, 255),
+        CustomColor::new(255, 0, 255),
+        CustomColor::new(0, 255, 255),
+        CustomColor::new(255, 255, 255),
+    ]);
+
+    /// The colors of the VGA text mode, as used by the Linux console.
+    pub const VGA: Self = Self::new([
+        CustomColor::new(0, 0, 0),
+        CustomColor::new(170, 0, 0),
+        CustomColor::new(0, 170, 0),
+        CustomColor::new(170, 85, 0),
+        CustomColor::new(0, 0, 170),
+        CustomColor::new(170, 0, 170),
+        CustomColor::new(0, 170, 170),
+        CustomColor::new(170, 170, 170),
+        CustomColor::new(85, 85, 85),
+        CustomColor::new(255, 85, 85),
+        CustomColor::new(85, 255, 85),
+        CustomColor::new(255, 255, 85),
+        CustomColor::new(85, 85, 255),
+        CustomColor::new(255, 85, 255),
+        CustomColor::new(85, 255, 255),
+        CustomColor::new(255, 255, 255),
+    ]);
+
+    /// The "Campbell" colors of the Windows 10 console and of Windows
+    /// Terminal.
+    pub const WINDOWS_10: Self = Self::new([
+        CustomColor::new(12, 12, 12),
+        CustomColor::new(197, 15, 31),
+        CustomColor::new(19, 161, 14),
+        CustomColor::new(193, 156, 0),
+        CustomColor::new(0, 55, 218),
+        CustomColor::new(136, 23, 152),
+        CustomColor::new(58, 150, 221),
+        CustomColor::new(204, 204, 204),
+        CustomColor::new(118, 118, 118),
+        CustomColor::new(231, 72, 86),
+        CustomColor::new(22, 198, 12),
+        CustomColor::new(249, 241, 165),
+        CustomColor::new(59, 120, 255),
+        CustomColor::new(180, 0, 158),
+        CustomColor::new(97, 214, 214),
+        CustomColor::new(242, 242, 242),
+    ]);
+
+    /// The default colors of macOS' Terminal.app.
+    pub const TERMINAL_APP: Self = Self::new([
+        CustomColor::new(0, 0, 0),
+        CustomColor::new(194, 54, 33),
+        CustomColor::new(37, 188, 36),
+        CustomColor::new(173, 173, 39),
+        CustomColor::new(73, 46, 225),
+        CustomColor::new(211, 56, 211),
+        CustomColor::new(51, 187, 200),
+        CustomColor::new(203, 204, 205),
+        CustomColor::new(129, 131, 131),
+        CustomColor::new(252, 57, 31),
+        CustomColor::new(49, 231, 34),
+        CustomColor::new(234, 236, 35),
+        CustomColor::new(88, 51, 255),
+        CustomColor::new(249, 53, 248),
+        CustomColor::new(20, 240, 240),
+        CustomColor::new(233, 235, 235),
+    ]);
+
+    /// The Solarized colors, mapped to the 16 standard colors the way the
+    /// Solarized terminal themes do.
+    pub const SOLARIZED: Self = Self::new([
+        CustomColor::new(7, 54, 66),
+        CustomColor::new(220, 50, 47),
+        CustomColor::new(133, 153, 0),
+        CustomColor::new(181, 137, 0),
+        CustomColor::new(38, 139, 210),
+        CustomColor::new(211, 54, 130),
+        CustomColor::new(42, 161, 152),
+        CustomColor::new(238, 232, 213),
+        CustomColor::new(0, 43, 54),
+        CustomColor::new(203, 75, 22),
+        CustomColor::new(88, 110, 117),
+        CustomColor::new(101, 123, 131),
+        CustomColor::new(131, 148, 150),
+        CustomColor::new(108, 113, 196),
+        CustomColor::new(147, 161, 161),
+        CustomColor::new(253, 246, 227),
+    ]);
+
+    /// Creates a palette from the 16 standard colors, in the order of their
+    /// ANSI index (black, red, ..., bright white). The rest of the 256 color
+    /// palette is filled with the usual 6x6x6 color cube and grayscale ramp.
+    #[must_use]
+    pub const fn new(basic: [CustomColor; 16]) -> Self {
+        let mut colors = [CustomColor::new(0, 0, 0); 256];
+        let mut index = 0;
+        while index < 16 {
+            colors[index] = basic[index];
+            index += 1;
+        }
+        while index < 232 {
+            let cube = index - 16;
+            colors[index] = CustomColor::new(
+                CUBE_LEVELS[cube / 36],
+                CUBE_LEVELS[cube / 6 % 6],
+                CUBE_LEVELS[cube % 6],
+            );
+            index += 1;
+        }
+        while index < 256 {
+            #[allow(clippy::cast_possible_truncation)]
+            let level = 8 + (index - 232) as u8 * 10;
+            colors[index] = CustomColor::new(level, level, level);
+            index += 1;
+        }
+        Self { colors }
+    }
+
+    /// Creates a palette from all the entries of the 256 color palette.
+    #[must_use]
+    pub const fn from_ansi256(colors: [CustomColor; 256]) -> Self {
+        Self { colors }
+    }
+
+    /// Gets the RGB value of an index of the palette.
+    #[must_use]
+    pub const fn get(&self, index: u8) -> CustomColor {
+        self.colors[index as usize]
+    }
+
+    /// Changes the RGB value of an index of the palette.
+    pub fn set(&mut self, index: u8, color: CustomColor) {
+        self.colors[usize::from(index)] = color;
+    }
+
+    /// Gets the 16 standard colors of the palette, in the order of their ANSI
+    /// index.
+    #[must_use]
+    pub fn basic(&self) -> &[CustomColor] {
+        &self.colors[..16]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::XTERM
+    }
+}
+
+impl From<[CustomColor; 16]> for Palette {
+    fn from(basic: [CustomColor; 16]) -> Self {
+        Self::new(basic)
+    }
+}
+
+impl From<[CustomColor; 256]> for Palette {
+    fn from(colors: [CustomColor; 256]) -> Self {
+        Self::from_ansi256(colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_colors() {
+        let palette = Palette::VGA;
+        assert_eq!(palette.get(15), CustomColor::new(255, 255, 255));
+        assert_eq!(palette.get(16), CustomColor::new(0, 0, 0));
+        assert_eq!(palette.get(21), CustomColor::new(0, 0, 255));
+        assert_eq!(palette.get(208), CustomColor::new(255, 135, 0));
+        assert_eq!(palette.get(231), CustomColor::new(255, 255, 255));
+        assert_eq!(palette.get(232), CustomColor::new(8, 8, 8));
+        assert_eq!(palette.get(255), CustomColor::new(238, 238, 238));
+    }
+
+    #[test]
+    fn basic_colors() {
+        let mut palette = Palette::default();
+        assert_eq!(palette, Palette::XTERM);
+        assert_eq!(palette.basic()[4], CustomColor::new(0, 0, 238));
+
+        palette.set(4, CustomColor::new(1, 2, 3));
+        assert_eq!(palette.get(4), CustomColor::new(1, 2, 3));
+        assert_eq!(palette.basic()[4], CustomColor::new(1, 2, 3));
+    }
+
+    #[test]
+    fn from_arrays() {
+        let basic: [CustomColor; 16] = Palette::SOLARIZED.basic().try_into().unwrap();
+        assert_eq!(Palette::from(basic), Palette::SOLARIZED);
+        let all = [CustomColor::new(1, 2, 3); 256];
+        assert_eq!(Palette::from(all).get(200), CustomColor::new(1, 2, 3));
+    }
+}
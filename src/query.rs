@@ -0,0 +1,586 @@
+//! Queries of the colors of the terminal, with the OSC 4, 10 and 11 escape
+//! sequences.
+
+use crate::{control, Color, CustomColor, Palette, QueryError};
+use std::{
+    env,
+    io::{self, IsTerminal},
+    time::{Duration, Instant},
+};
+
+/// The time given to the terminal to answer, by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The Primary Device Attributes query, which all terminals answer. It is
+/// sent after the color queries: terminals answer in order, so getting its
+/// answer first means that the color queries are not supported, without
+/// having to wait for the timeout.
+const DEVICE_ATTRIBUTES: &[u8] = b"\x1b[c";
+
+/// A terminal that can be queried for its colors.
+///
+/// It is implemented for the controlling terminal by the `query_*` functions
+/// of [`control`](crate::control), and can be implemented by a stand-in
+/// answering the queries, e.g. in tests.
+pub trait QueryTerminal {
+    /// Writes the queries to the terminal.
+    ///
+    /// # Errors
+    /// Fails if writing to the terminal fails.
+    fn write_query(&mut self, query: &[u8]) -> io::Result<()>;
+
+    /// Reads the answers of the terminal into `buf`, waiting at most
+    /// `timeout` for them. Returns `Ok(0)` if nothing came in time.
+    ///
+    /// # Errors
+    /// Fails if reading from the terminal fails.
+    fn read_reply(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize>;
+}
+
+/// Queries the colors of a [`QueryTerminal`].
+///
+/// The colors are returned as [`Color::TrueColor`]s.
+///
+/// ```rust
+/// use colored::control::{QueryTerminal, TerminalQuery};
+/// use colored::Color;
+/// use std::{io, time::Duration};
+///
+/// /// A terminal with a dark background, answering everything at once.
+/// struct Dark(Vec<u8>);
+///
+/// impl QueryTerminal for Dark {
+///     fn write_query(&mut self, _query: &[u8]) -> io::Result<()> {
+///         self.0 = b"\x1b]11;rgb:1c1c/1c1c/1c1c\x1b\\\x1b[?62;22c".to_vec();
+///         Ok(())
+///     }
+///
+///     fn read_reply(&mut self, buf: &mut [u8], _timeout: Duration) -> io::Result<usize> {
+///         let len = self.0.len().min(buf.len());
+///         buf[..len].copy_from_slice(&self.0[..len]);
+///         self.0.drain(..len);
+///         Ok(len)
+///     }
+/// }
+///
+/// let background = TerminalQuery::new(Dark(Vec::new())).background().unwrap();
+/// assert_eq!(background, Color::TrueColor { r: 28, g: 28, b: 28 });
+/// ```
+#[derive(Debug)]
+pub struct TerminalQuery<T> {
+    terminal: T,
+    timeout: Duration,
+}
+
+impl<T: QueryTerminal> TerminalQuery<T> {
+    /// Queries a terminal, waiting 100ms for its answers by default.
+    #[must_use]
+    pub const fn new(terminal: T) -> Self {
+        Self {
+            terminal,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Changes how long to wait for the answers of the terminal.
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Queries the default foreground color with OSC 10.
+    ///
+    /// # Errors
+    /// Fails if the terminal doesn't answer in time or doesn't support the
+    /// query.
+    pub fn foreground(&mut self) -> Result<Color, QueryError> {
+        self.query_one("10")
+    }
+
+    /// Queries the default background color with OSC 11.
+    ///
+    /// # Errors
+    /// Fails if the terminal doesn't answer in time or doesn't support the
+    /// query.
+    pub fn background(&mut self) -> Result<Color, QueryError> {
+        self.query_one("11")
+    }
+
+    /// Queries an index of the 256 color palette with OSC 4.
+    ///
+    /// # Errors
+    /// Fails if the terminal doesn't answer in time or doesn't support the
+    /// query.
+    pub fn ansi_color(&mut self, index: u8) -> Result<Color, QueryError> {
+        self.query_one(&format!("4;{index}"))
+    }
+
+    /// Queries the 16 standard colors with OSC 4, all at once.
+    ///
+    /// # Errors
+    /// Fails if the terminal doesn't answer in time or doesn't support the
+    /// query.
+    pub fn palette(&mut self) -> Result<Palette, QueryError> {
+        let queries: Vec<String> = (0..16).map(|index| format!("4;{index}")).collect();
+        let colors = self.query(&queries)?;
+        let mut basic = [CustomColor::new(0, 0, 0); 16];
+        for (rgb, color) in basic.iter_mut().zip(colors) {
+            if let Color::TrueColor { r, g, b } = color {
+                *rgb = CustomColor::new(r, g, b);
+            }
+        }
+        Ok(Palette::new(basic))
+    }
+
+    fn query_one(&mut self, query: &str) -> Result<Color, QueryError> {
+        let colors = self.query(&[query.to_owned()])?;
+        colors.into_iter().next().ok_or(QueryError::Unsupported)
+    }
+
+    /// Sends the queries, identified by the parameters of their OSC
+    /// sequence, and gets the colors they are answered with.
+    fn query(&mut self, queries: &[String]) -> Result<Vec<Color>, QueryError> {
+        let mut request = Vec::new();
+        for query in queries {
+            request.extend_from_slice(format!("\x1b]{query};?\x1b\\").as_bytes());
+        }
+        request.extend_from_slice(DEVICE_ATTRIBUTES);
+        self.terminal.write_query(&request)?;
+
+        let deadline = Instant::now() + self.timeout;
+        let mut reply = Vec::new();
+        let mut buf = [0; 256];
+        let attributes_received = loop {
+            if let Some(end) = find_device_attributes(&reply) {
+                reply.truncate(end);
+                break true;
+            }
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.terminal.read_reply(&mut buf, timeout)? {
+                0 => break false,
+                len => reply.extend_from_slice(&buf[..len]),
+            }
+        };
+
+        let answers = osc_answers(&reply);
+        queries
+            .iter()
+            .map(|query| {
+                let answer = answers.iter().find_map(|answer| {
+                    answer
+                        .strip_prefix(query.as_str())
+                        .and_then(|rest| rest.strip_prefix(';'))
+                });
+                match answer {
+                    Some(spec) => parse_color_spec(spec)
+                        .ok_or_else(|| QueryError::InvalidReply(spec.to_owned())),
+                    None if attributes_received => Err(QueryError::Unsupported),
+                    None => Err(QueryError::Timeout),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Finds the start of the answer to the Primary Device Attributes query,
+/// `CSI ? ... c`, as long as it was fully received.
+fn find_device_attributes(reply: &[u8]) -> Option<usize> {
+    let start = reply.windows(3).position(|window| window == b"\x1b[?")?;
+    let params = &reply[start + 3..];
+    let end = params.iter().position(|&byte| byte == b'c')?;
+    params[..end]
+        .iter()
+        .all(|&byte| byte.is_ascii_digit() || byte == b';')
+        .then_some(start)
+}
+
+/// Gets the content of the OSC sequences of a reply, terminated by either
+/// `BEL` or `ST`.
+fn osc_answers(reply: &[u8]) -> Vec<String> {
+    let reply = String::from_utf8_lossy(reply);
+    reply
+        .split("\x1b]")
+        .skip(1)
+        .filter_map(|answer| {
+            let end = answer.find(['\x07', '\x1b'])?;
+            Some(answer[..end].to_owned())
+        })
+        .collect()
+}
+
+/// Parses an X11 color specification, `rgb:r/g/b` with 1 to 4 hex digits
+/// per component, as answered by terminals.
+fn parse_color_spec(spec: &str) -> Option<Color> {
+    let components = spec
+        .strip_prefix("rgb:")
+        .or_else(|| spec.strip_prefix("rgba:"))?;
+    let mut components = components.split('/').map(|component| {
+        if !(1..=4).contains(&component.len()) {
+            return None;
+        }
+        let value = u32::from_str_radix(component, 16).ok()?;
+        let max = (1 << (4 * component.len())) - 1;
+        u8::try_from((value * 255 + max / 2) / max).ok()
+    });
+    let r = components.next()??;
+    let g = components.next()??;
+    let b = components.next()??;
+    Some(Color::TrueColor { r, g, b })
+}
+
+/// Parses `COLORFGBG`, e.g. `15;0` or `15;default;0`, into the ANSI indices
+/// of the foreground and background colors.
+fn parse_colorfgbg(value: &str) -> Option<(u8, u8)> {
+    let mut fields = value.split(';');
+    let foreground = fields.next()?.parse().ok()?;
+    let background = fields.next_back()?.parse().ok()?;
+    Some((foreground, background))
+}
+
+/// Gets the foreground and background colors from `COLORFGBG`, resolved with
+/// the current [`Palette`].
+pub fn colorfgbg() -> Option<(Color, Color)> {
+    let (foreground, background) = parse_colorfgbg(&env::var("COLORFGBG").ok()?)?;
+    control::with_current_palette(|palette| {
+        let color = |index| {
+            let CustomColor { r, g, b } = palette.get(index);
+            Color::TrueColor { r, g, b }
+        };
+        Some((color(foreground), color(background)))
+    })
+}
+
+/// Queries the controlling terminal, if both the standard input and output
+/// are terminals.
+fn query_tty<R>(
+    query: impl FnOnce(&mut TerminalQuery<tty::Tty>) -> Result<R, QueryError>,
+) -> Result<R, QueryError> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(QueryError::NotATerminal);
+    }
+    query(&mut TerminalQuery::new(tty::Tty::open()?))
+}
+
+/// Queries the default foreground color of the terminal, falling back to
+/// `COLORFGBG` if the terminal cannot be queried.
+///
+/// # Errors
+/// Fails if the standard input or output is not a terminal, or if the
+/// terminal doesn't answer, and `COLORFGBG` isn't set.
+pub fn query_foreground() -> Result<Color, QueryError> {
+    query_tty(TerminalQuery::foreground)
+        .or_else(|error| colorfgbg().map(|(foreground, _)| foreground).ok_or(error))
+}
+
+/// Queries the default background color of the terminal, falling back to
+/// `COLORFGBG` if the terminal cannot be queried.
+///
+/// ```rust,no_run
+/// use colored::{control, Color};
+/// let dark = match control::query_background() {
+///     Ok(background) => background.relative_luminance() < 0.5,
+///     Err(_) => true,
+/// };
+/// ```
+///
+/// # Errors
+/// Fails if the standard input or output is not a terminal, or if the
+/// terminal doesn't answer, and `COLORFGBG` isn't set.
+pub fn query_background() -> Result<Color, QueryError> {
+    query_tty(TerminalQuery::background)
+        .or_else(|error| colorfgbg().map(|(_, background)| background).ok_or(error))
+}
+
+/// Queries an index of the 256 color palette of the terminal.
+///
+/// # Errors
+/// Fails if the standard input or output is not a terminal, or if the
+/// terminal doesn't answer.
+pub fn query_ansi_color(index: u8) -> Result<Color, QueryError> {
+    query_tty(|query| query.ansi_color(index))
+}
+
+/// Queries the 16 standard colors of the terminal, e.g. to pass them to
+/// [`set_palette`](crate::control::set_palette).
+///
+/// # Errors
+/// Fails if the standard input or output is not a terminal, or if the
+/// terminal doesn't answer.
+pub fn query_palette() -> Result<Palette, QueryError> {
+    query_tty(TerminalQuery::palette)
+}
+
+#[cfg(unix)]
+mod tty {
+    use super::QueryTerminal;
+    use crate::QueryError;
+    use std::{
+        fs::{File, OpenOptions},
+        io::{self, Read, Write},
+        mem::MaybeUninit,
+        os::fd::AsRawFd,
+        time::Duration,
+    };
+
+    /// The controlling terminal, in raw mode until dropped.
+    pub struct Tty {
+        file: File,
+        original: libc::termios,
+    }
+
+    impl Tty {
+        pub fn open() -> Result<Self, QueryError> {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/tty")
+                .map_err(|_| QueryError::NotATerminal)?;
+            let fd = file.as_raw_fd();
+            #[allow(unsafe_code)] // needed to switch the terminal to raw mode
+            let original = unsafe {
+                let mut original = MaybeUninit::uninit();
+                if libc::tcgetattr(fd, original.as_mut_ptr()) != 0 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                let original = original.assume_init();
+                let mut raw = original;
+                raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+                raw.c_cc[libc::VMIN] = 0;
+                raw.c_cc[libc::VTIME] = 0;
+                if libc::tcsetattr(fd, libc::TCSANOW, &raw) != 0 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                original
+            };
+            Ok(Self { file, original })
+        }
+    }
+
+    impl Drop for Tty {
+        fn drop(&mut self) {
+            #[allow(unsafe_code)] // needed to restore the terminal
+            unsafe {
+                libc::tcsetattr(self.file.as_raw_fd(), libc::TCSANOW, &self.original);
+            }
+        }
+    }
+
+    impl QueryTerminal for Tty {
+        fn write_query(&mut self, query: &[u8]) -> io::Result<()> {
+            self.file.write_all(query)?;
+            self.file.flush()
+        }
+
+        fn read_reply(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize> {
+            let mut fds = libc::pollfd {
+                fd: self.file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+            #[allow(unsafe_code)] // needed to wait for the answer with a timeout
+            let ready = unsafe { libc::poll(&mut fds, 1, timeout) };
+            match ready {
+                0 => Ok(0),
+                1.. => self.file.read(buf),
+                _ => Err(io::Error::last_os_error()),
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod tty {
+    use super::QueryTerminal;
+    use crate::QueryError;
+    use std::{io, time::Duration};
+
+    /// Querying the terminal is not supported on this platform.
+    pub enum Tty {}
+
+    impl Tty {
+        pub fn open() -> Result<Self, QueryError> {
+            Err(QueryError::Unsupported)
+        }
+    }
+
+    impl QueryTerminal for Tty {
+        fn write_query(&mut self, _query: &[u8]) -> io::Result<()> {
+            match *self {}
+        }
+
+        fn read_reply(&mut self, _buf: &mut [u8], _timeout: Duration) -> io::Result<usize> {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in for a terminal, answering the queries it supports with
+    /// the colors of a palette.
+    struct FakeTerminal {
+        palette: Palette,
+        foreground: Option<&'static str>,
+        background: Option<&'static str>,
+        answers_palette: bool,
+        answers_attributes: bool,
+        pending: Vec<u8>,
+        queries: Vec<String>,
+    }
+
+    impl FakeTerminal {
+        fn new() -> Self {
+            Self {
+                palette: Palette::SOLARIZED,
+                foreground: Some("rgb:8383/9494/9696"),
+                background: Some("rgb:0000/2b2b/3636"),
+                answers_palette: true,
+                answers_attributes: true,
+                pending: Vec::new(),
+                queries: Vec::new(),
+            }
+        }
+    }
+
+    impl QueryTerminal for FakeTerminal {
+        fn write_query(&mut self, query: &[u8]) -> io::Result<()> {
+            let query = String::from_utf8(query.to_vec()).unwrap();
+            for osc in query.split("\x1b]").skip(1) {
+                let params = osc.split(";?").next().unwrap();
+                self.queries.push(params.to_owned());
+                let answer = match params {
+                    "10" => self.foreground.map(str::to_owned),
+                    "11" => self.background.map(str::to_owned),
+                    index if self.answers_palette => {
+                        let index = index.strip_prefix("4;").unwrap().parse().unwrap();
+                        let CustomColor { r, g, b } = self.palette.get(index);
+                        Some(format!("rgb:{r:02x}{r:02x}/{g:02x}{g:02x}/{b:02x}{b:02x}"))
+                    }
+                    _ => None,
+                };
+                if let Some(answer) = answer {
+                    // alternate between the two possible terminators
+                    let terminator = if self.queries.len() % 2 == 0 {
+                        "\x07"
+                    } else {
+                        "\x1b\\"
+                    };
+                    let answer = format!("\x1b]{params};{answer}{terminator}");
+                    self.pending.extend_from_slice(answer.as_bytes());
+                }
+            }
+            if self.answers_attributes {
+                self.pending.extend_from_slice(b"\x1b[?64;1;9;15;22c");
+            }
+            Ok(())
+        }
+
+        fn read_reply(&mut self, buf: &mut [u8], _timeout: Duration) -> io::Result<usize> {
+            // answer a few bytes at a time, like a slow terminal
+            let len = self.pending.len().min(buf.len()).min(7);
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn foreground_and_background() {
+        let mut query = TerminalQuery::new(FakeTerminal::new());
+        assert_eq!(
+            query.foreground().unwrap(),
+            Color::TrueColor {
+                r: 131,
+                g: 148,
+                b: 150
+            }
+        );
+        assert_eq!(
+            query.background().unwrap(),
+            Color::TrueColor { r: 0, g: 43, b: 54 }
+        );
+        assert_eq!(query.terminal.queries, ["10", "11"]);
+    }
+
+    #[test]
+    fn palette() {
+        let mut query = TerminalQuery::new(FakeTerminal::new());
+        assert_eq!(query.palette().unwrap(), Palette::SOLARIZED);
+        assert_eq!(
+            query.ansi_color(4).unwrap(),
+            Color::TrueColor {
+                r: 38,
+                g: 139,
+                b: 210
+            }
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        let mut terminal = FakeTerminal::new();
+        terminal.background = None;
+        terminal.answers_palette = false;
+        let mut query = TerminalQuery::new(terminal);
+        assert!(matches!(query.background(), Err(QueryError::Unsupported)));
+        assert!(matches!(query.palette(), Err(QueryError::Unsupported)));
+        assert!(query.foreground().is_ok());
+    }
+
+    #[test]
+    fn timeout() {
+        let mut terminal = FakeTerminal::new();
+        terminal.background = None;
+        terminal.answers_attributes = false;
+        let mut query = TerminalQuery::new(terminal).timeout(Duration::from_millis(1));
+        assert!(matches!(query.background(), Err(QueryError::Timeout)));
+        // the answer is used even if the device attributes never come
+        assert!(query.foreground().is_ok());
+    }
+
+    #[test]
+    fn invalid_reply() {
+        let mut terminal = FakeTerminal::new();
+        terminal.background = Some("rgb:zz/00/00");
+        let mut query = TerminalQuery::new(terminal);
+        let error = query.background().unwrap_err();
+        assert!(matches!(&error, QueryError::InvalidReply(reply) if reply == "rgb:zz/00/00"));
+    }
+
+    #[test]
+    fn color_specs() {
+        let spec = |spec| parse_color_spec(spec);
+        let rgb = |r, g, b| Some(Color::TrueColor { r, g, b });
+        assert_eq!(spec("rgb:ffff/8080/0000"), rgb(255, 128, 0));
+        assert_eq!(spec("rgb:ff/80/00"), rgb(255, 128, 0));
+        assert_eq!(spec("rgb:f/8/0"), rgb(255, 136, 0));
+        assert_eq!(spec("rgb:fff/800/000"), rgb(255, 128, 0));
+        assert_eq!(spec("rgba:ffff/8080/0000/ffff"), rgb(255, 128, 0));
+        assert_eq!(spec("rgb:ffff/8080"), None);
+        assert_eq!(spec("rgb:fffff/8080/0000"), None);
+        assert_eq!(spec("#ff8000"), None);
+    }
+
+    #[test]
+    fn colorfgbg() {
+        assert_eq!(parse_colorfgbg("15;0"), Some((15, 0)));
+        assert_eq!(parse_colorfgbg("0;default;15"), Some((0, 15)));
+        assert_eq!(parse_colorfgbg("15"), None);
+        assert_eq!(parse_colorfgbg("default;0"), None);
+        assert_eq!(parse_colorfgbg(""), None);
+    }
+
+    #[test]
+    fn device_attributes() {
+        assert_eq!(find_device_attributes(b"\x1b[?62;22c"), Some(0));
+        assert_eq!(
+            find_device_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?1;2c"),
+            Some(15)
+        );
+        assert_eq!(find_device_attributes(b"\x1b[?62;2"), None);
+        assert_eq!(find_device_attributes(b"\x1b[?6x2c"), None);
+    }
+}
@@ -0,0 +1,645 @@
+use crate::{
+    ansi, control,
+    style::{self, UnderlineStyle},
+    write_hyperlink_start, Color, ColoredString, Style, Styles, HYPERLINK_END,
+};
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How a [`ColoredString`] turns off its attributes after its text.
+///
+/// The mode used by default is [`ResetMode::Full`]. It can be changed with
+/// [`control::set_reset_mode`](crate::control::set_reset_mode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Resets all the attributes with `ESC[0m`, also turning off those set
+    /// around the string, e.g. by another library.
+    #[default]
+    Full,
+    /// Only turns off the attributes the string set, with the targeted codes
+    /// (`22` for bold, `39` for the foreground color, ...), so that the
+    /// attributes set around it are kept.
+    ///
+    /// ```rust
+    /// use colored::{control, Colorize, ResetMode};
+    /// # control::set_override(true);
+    /// control::set_reset_mode(ResetMode::Targeted);
+    /// // the underline set around the string is kept
+    /// let text = format!("\x1B[4m{} link", "see".bold().red());
+    /// # if cfg!(not(feature = "no-color")) {
+    /// assert_eq!(text, "\x1B[4m\x1B[1;31msee\x1B[22;39m link");
+    /// # }
+    /// ```
+    Targeted,
+}
+
+/// Where a color is set: in the foreground, the background, or on the
+/// underline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Foreground,
+    Background,
+    Underline,
+}
+
+impl Layer {
+    /// The code of the extended colors (`38`, `48` or `58`), which is
+    /// followed by the code of the default color.
+    pub const fn extended(self) -> u8 {
+        match self {
+            Self::Foreground => 38,
+            Self::Background => 48,
+            Self::Underline => 58,
+        }
+    }
+}
+
+/// The SGR parameters setting a color, which are written without being
+/// formatted into a `String` first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorCode {
+    /// A single code, e.g. `31` or `39`.
+    Basic(u8),
+    /// A color of the 256 color palette, e.g. `38;5;208`.
+    Ansi(Layer, u8),
+    /// A truecolor, e.g. `38;2;255;128;0`.
+    Rgb(Layer, u8, u8, u8),
+}
+
+impl fmt::Display for ColorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Basic(code) => write!(f, "{code}"),
+            Self::Ansi(layer, index) => write!(f, "{};5;{index}", layer.extended()),
+            Self::Rgb(layer, r, g, b) => write!(f, "{};2;{r};{g};{b}", layer.extended()),
+        }
+    }
+}
+
+/// Writes the parameters of an SGR sequence, starting the sequence before
+/// the first one and separating the next ones.
+struct Sgr<'a, W: ?Sized> {
+    out: &'a mut W,
+    started: bool,
+}
+
+impl<'a, W: fmt::Write + ?Sized> Sgr<'a, W> {
+    fn new(out: &'a mut W) -> Self {
+        Self {
+            out,
+            started: false,
+        }
+    }
+
+    fn push(&mut self, param: impl fmt::Display) -> fmt::Result {
+        self.out
+            .write_str(if self.started { ";" } else { "\x1B[" })?;
+        self.started = true;
+        write!(self.out, "{param}")
+    }
+
+    /// Ends the sequence, if it was started, and returns whether it was.
+    fn finish(self) -> Result<bool, fmt::Error> {
+        if self.started {
+            self.out.write_char('m')?;
+        }
+        Ok(self.started)
+    }
+}
+
+/// The SGR attributes of a text as they are written: the colors are
+/// transformed and downgraded first, so that colors written the same way
+/// are equal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Attributes {
+    /// The styles, without the underline when it is written with its style.
+    style: Style,
+    /// The style of the underline, if extended underlines are supported.
+    underline: Option<UnderlineStyle>,
+    bgcolor: Option<ColorCode>,
+    fgcolor: Option<ColorCode>,
+    underline_color: Option<ColorCode>,
+}
+
+impl Attributes {
+    pub fn new(string: &ColoredString, fgcolor: Option<Color>, bgcolor: Option<Color>) -> Self {
+        let extended_underline =
+            string.style.contains(Styles::Underline) && control::extended_underlines();
+        let mut style = string.style;
+        if extended_underline {
+            style.remove(Styles::Underline);
+        }
+
+        let transform = control::cvd_transform();
+        let code = |color: Color, layer: Layer| {
+            transform
+                .map_or(color, |transform| transform.apply(color))
+                .code(layer)
+        };
+        Self {
+            style,
+            underline: extended_underline.then_some(string.underline_style),
+            bgcolor: bgcolor.map(|color| code(color, Layer::Background)),
+            fgcolor: fgcolor.map(|color| code(color, Layer::Foreground)),
+            underline_color: string
+                .underline_color
+                .filter(|_| extended_underline)
+                .map(|color| code(color, Layer::Underline)),
+        }
+    }
+
+    /// Writes the SGR sequence changing the attributes from `previous` to
+    /// these ones, turning off what is no longer set with the targeted codes
+    /// (`22`, `39`, ...) instead of a reset. Returns whether there was
+    /// anything to write.
+    pub fn write_from<W: fmt::Write + ?Sized>(
+        &self,
+        previous: &Self,
+        out: &mut W,
+    ) -> Result<bool, fmt::Error> {
+        let with_underline = |attributes: &Self| {
+            let mut style = attributes.style;
+            if attributes.underline.is_some() {
+                style.add(Styles::Underline);
+            }
+            style
+        };
+        let (before, after) = (with_underline(previous), with_underline(self));
+        let mut sgr = Sgr::new(out);
+        let mut remaining = before;
+        for code in before.off_codes(after) {
+            sgr.push(code)?;
+            remaining &= !before.turned_off_by(code);
+        }
+
+        for code in (self.style & !remaining).codes() {
+            sgr.push(code)?;
+        }
+        if let Some(underline) = self.underline {
+            if !remaining.contains(Styles::Underline) || previous.underline != self.underline {
+                sgr.push(underline.to_str())?;
+            }
+        }
+
+        let colors = [
+            (previous.bgcolor, self.bgcolor, 49),
+            (previous.fgcolor, self.fgcolor, 39),
+            (previous.underline_color, self.underline_color, 59),
+        ];
+        for (previous, color, off) in colors {
+            if previous != color {
+                sgr.push(color.unwrap_or(ColorCode::Basic(off)))?;
+            }
+        }
+        sgr.finish()
+    }
+
+    /// Writes the SGR sequence turning off these attributes, depending on
+    /// the [`ResetMode`].
+    pub fn write_reset<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        match control::reset_mode() {
+            ResetMode::Full => out.write_str("\x1B[0m"),
+            ResetMode::Targeted => Self::default().write_from(self, out).map(drop),
+        }
+    }
+
+    /// Writes the SGR sequence setting these attributes back after an SGR
+    /// sequence with the parameters `params`, found in a text nested in a
+    /// string with these attributes, turned some of them off: all of them
+    /// after `0`, or e.g. the foreground color after a `39` ending a color
+    /// set by the nested text. A `39` setting the default color is kept.
+    pub fn write_restored_after<W: fmt::Write + ?Sized>(
+        &self,
+        params: &str,
+        nested: &mut Nested,
+        out: &mut W,
+    ) -> fmt::Result {
+        let mut restored = Self::default();
+        let mut params = params.split(';');
+        while let Some(param) = params.next() {
+            let mut subparams = param.split(':');
+            let code = match subparams.next() {
+                Some("") | None => 0,
+                Some(code) => code.parse().unwrap_or(u8::MAX),
+            };
+            // `4:0` turns the underline off like `24`
+            let code = if code == 4 && subparams.next() == Some("0") {
+                24
+            } else {
+                code
+            };
+            match code {
+                0 => {
+                    restored = *self;
+                    *nested = Nested::default();
+                }
+                39 if nested.fgcolor => {
+                    restored.fgcolor = self.fgcolor;
+                    nested.fgcolor = false;
+                }
+                49 if nested.bgcolor => {
+                    restored.bgcolor = self.bgcolor;
+                    nested.bgcolor = false;
+                }
+                59 if nested.underline_color => {
+                    restored.underline_color = self.underline_color;
+                    nested.underline_color = false;
+                }
+                // the colors set after an off code are kept
+                30..=38 | 90..=97 => {
+                    restored.fgcolor = None;
+                    nested.fgcolor = true;
+                }
+                40..=48 | 100..=107 => {
+                    restored.bgcolor = None;
+                    nested.bgcolor = true;
+                }
+                58 => {
+                    restored.underline_color = None;
+                    nested.underline_color = true;
+                }
+                code => {
+                    let ended = nested.style.turned_off_by(code);
+                    if ended != style::CLEAR {
+                        nested.style &= !ended;
+                        restored.style |= self.style.turned_off_by(code);
+                        if code == 24 {
+                            restored.underline = self.underline;
+                        }
+                    }
+                    nested.style |= Style::set_by(code);
+                }
+            }
+            // skip the arguments of the `;` separated extended colors
+            if matches!(code, 38 | 48 | 58) && !param.contains(':') {
+                let arguments = match params.next() {
+                    Some("5") => 1,
+                    Some("2") => 3,
+                    _ => 0,
+                };
+                params.by_ref().take(arguments).for_each(drop);
+            }
+        }
+        restored.write_from(&Self::default(), out).map(drop)
+    }
+}
+
+/// The attributes set by the SGR sequences of a text nested in a colored
+/// string, to tell the codes ending them from those setting e.g. the
+/// default color.
+#[derive(Debug, Default)]
+pub struct Nested {
+    style: Style,
+    fgcolor: bool,
+    bgcolor: bool,
+    underline_color: bool,
+}
+
+/// Writes the `text` of a string with gradients one grapheme cluster at a
+/// time, with the colors of the gradients. The escape sequence is only
+/// repeated when the rendered colors change, e.g. when they are downgraded
+/// to the same color.
+///
+/// The escape sequences of the text are written as they are, and the colors
+/// of a nested string are kept until it ends, see
+/// [`Attributes::write_restored_after`].
+pub fn write_gradient<W: fmt::Write + ?Sized>(
+    string: &ColoredString,
+    text: &str,
+    hyperlink: Option<&str>,
+    out: &mut W,
+) -> fmt::Result {
+    // the text between the escape sequences, each with the sequence ending
+    // it, if any
+    let pieces = || {
+        let mut start = 0;
+        ansi::sequences(text)
+            .map(Some)
+            .chain([None])
+            .map(move |range| {
+                let end = range.as_ref().map_or(text.len(), |range| range.start);
+                let piece = (&text[start..end], range.clone().map(|range| &text[range]));
+                start = range.map_or(text.len(), |range| range.end);
+                piece
+            })
+    };
+    let count = pieces().map(|(text, _)| text.graphemes(true).count()).sum();
+
+    let mut current = Attributes::default();
+    let mut nested = Nested::default();
+    let mut index = 0;
+    for (text, sequence) in pieces() {
+        for grapheme in text.graphemes(true) {
+            let (fgcolor, bgcolor) = string.colors_of(index, count);
+            index += 1;
+            let mut attributes = Attributes::new(string, fgcolor, bgcolor);
+            if nested.fgcolor {
+                attributes.fgcolor = None;
+            }
+            if nested.bgcolor {
+                attributes.bgcolor = None;
+            }
+            if attributes != current {
+                if !attributes.write_from(&Attributes::default(), out)? {
+                    attributes.write_from(&current, out)?;
+                }
+                current = attributes;
+            }
+            out.write_str(grapheme)?;
+        }
+
+        let Some(sequence) = sequence else {
+            continue;
+        };
+        out.write_str(sequence)?;
+        if let Some(params) = ansi::sgr_params(sequence) {
+            current.write_restored_after(params, &mut nested, out)?;
+            // the colors set by the nested string replace the gradients
+            if nested.fgcolor {
+                current.fgcolor = None;
+            }
+            if nested.bgcolor {
+                current.bgcolor = None;
+            }
+        } else if let Some(url) = hyperlink.filter(|_| sequence == HYPERLINK_END) {
+            write_hyperlink_start(out, url)?;
+        }
+    }
+    if current != Attributes::default() {
+        current.write_reset(out)?;
+    }
+    Ok(())
+}
+
+/// Writes colored strings one after another, keeping track of the
+/// attributes set in the terminal so that only the ones that change are
+/// written.
+///
+/// Each [`ColoredString`] written on its own starts with all its attributes
+/// and ends with a reset. The renderer instead turns off the attributes the
+/// next string doesn't have with the targeted codes (`22` for bold, `39`
+/// for the foreground color, ...), and only resets them all when it is
+/// [finished](Renderer::finish), following the [`ResetMode`].
+/// [`StyledText`](crate::StyledText) is displayed this way.
+///
+/// ```rust
+/// # use colored::*;
+/// # control::set_override(true);
+/// let mut renderer = Renderer::new(String::new());
+/// renderer.write(&"error".red().bold())?;
+/// renderer.write(&": ".red())?;
+/// renderer.write(&"not found".italic())?;
+/// let rendered = renderer.finish()?;
+/// # if cfg!(not(feature = "no-color")) {
+/// assert_eq!(rendered, "\x1B[1;31merror\x1B[22m: \x1B[3;39mnot found\x1B[0m");
+/// # }
+/// # control::unset_override();
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Renderer<W> {
+    out: W,
+    current: Attributes,
+    hyperlink: Option<String>,
+}
+
+impl<W: fmt::Write> Renderer<W> {
+    /// Creates a renderer writing to `out`, e.g. a `String` or a
+    /// `fmt::Formatter`.
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            current: Attributes::default(),
+            hyperlink: None,
+        }
+    }
+
+    /// Writes a colored string, after the sequences changing the attributes
+    /// of the previous one to its own.
+    ///
+    /// A string containing escape sequences, e.g. nested colored strings, is
+    /// written as usual after resetting the attributes, as its sequences
+    /// change them.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to the output fails.
+    pub fn write(&mut self, string: &ColoredString) -> fmt::Result {
+        if string.input.is_empty() {
+            return Ok(());
+        }
+        if !ColoredString::has_colors() {
+            return self.out.write_str(&string.input);
+        }
+        if string.input.contains('\x1B') {
+            self.reset()?;
+            return write!(self.out, "{string}");
+        }
+
+        let hyperlink = string.hyperlink.as_ref().filter(|_| control::hyperlinks());
+        if hyperlink != self.hyperlink.as_ref() {
+            self.set_hyperlink(hyperlink.cloned())?;
+        }
+        if string.has_gradient() {
+            let count = string.input.graphemes(true).count();
+            for (index, grapheme) in string.input.graphemes(true).enumerate() {
+                let (fgcolor, bgcolor) = string.colors_of(index, count);
+                self.set(Attributes::new(string, fgcolor, bgcolor))?;
+                self.out.write_str(grapheme)?;
+            }
+            Ok(())
+        } else {
+            self.set(Attributes::new(string, string.fgcolor, string.bgcolor))?;
+            self.out.write_str(&string.input)
+        }
+    }
+
+    /// Resets the attributes and ends the hyperlink, if they are set, and
+    /// returns the output.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing to the output fails.
+    pub fn finish(mut self) -> Result<W, fmt::Error> {
+        self.reset()?;
+        Ok(self.out)
+    }
+
+    fn set(&mut self, attributes: Attributes) -> fmt::Result {
+        attributes.write_from(&self.current, &mut self.out)?;
+        self.current = attributes;
+        Ok(())
+    }
+
+    fn set_hyperlink(&mut self, hyperlink: Option<String>) -> fmt::Result {
+        if self.hyperlink.is_some() {
+            self.out.write_str(HYPERLINK_END)?;
+        }
+        if let Some(url) = &hyperlink {
+            write_hyperlink_start(&mut self.out, url)?;
+        }
+        self.hyperlink = hyperlink;
+        Ok(())
+    }
+
+    fn reset(&mut self) -> fmt::Result {
+        if self.current != Attributes::default() {
+            self.current.write_reset(&mut self.out)?;
+        }
+        self.current = Attributes::default();
+        if self.hyperlink.is_some() {
+            self.set_hyperlink(None)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColorSupport, Colorize};
+
+    fn render(strings: &[ColoredString]) -> String {
+        let mut renderer = Renderer::new(String::new());
+        for string in strings {
+            renderer.write(string).unwrap();
+        }
+        renderer.finish().unwrap()
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn only_changes_are_written() {
+        control::with_override(true, || {
+            assert_eq!(render(&[]), "");
+            assert_eq!(render(&["a".into(), "b".into()]), "ab");
+            assert_eq!(
+                render(&["a".red(), "b".red(), "c".red().on_blue()]),
+                "\x1B[31mab\x1B[44mc\x1B[0m"
+            );
+            assert_eq!(
+                render(&["a".bold().dimmed().blue(), "b".dimmed(), "c".into()]),
+                "\x1B[1;2;34ma\x1B[22;2;39mb\x1B[22mc"
+            );
+            assert_eq!(
+                render(&[
+                    "a".italic().on_red(),
+                    "".green(),
+                    "b".on_red(),
+                    "c".normal()
+                ]),
+                "\x1B[3;41ma\x1B[23mb\x1B[49mc"
+            );
+            assert_eq!(
+                render(&["a".red(), "b".color(Color::Default)]),
+                "\x1B[31ma\x1B[39mb\x1B[0m"
+            );
+        });
+        let plain = control::with_override(false, || render(&["a".red(), "b".bold()]));
+        assert_eq!(plain, "ab");
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn rendered_strings_are_parsed_back() {
+        let strings = [
+            "error".red().bold(),
+            "[E0308]".red().on_black(),
+            ": ".normal(),
+            "mismatched".italic().strikethrough(),
+            " types".strikethrough().color(Color::AnsiColor(208)),
+        ];
+        // the colors are written as they are, without being downgraded
+        let rendered = control::with_override(true, || {
+            control::with_color_support(ColorSupport::Ansi256, || render(&strings))
+        });
+        assert_eq!(crate::ansi::parse(&rendered).segments, strings);
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn targeted_reset_at_the_end() {
+        let rendered = control::with_override(true, || {
+            control::with_reset_mode(ResetMode::Targeted, || render(&["a".red(), "b".bold()]))
+        });
+        assert_eq!(rendered, "\x1B[31ma\x1B[1;39mb\x1B[22m");
+    }
+
+    #[test]
+    fn restored_after() {
+        let outer = "".bold().red().on_blue();
+        let outer = Attributes::new(&outer, outer.fgcolor, outer.bgcolor);
+        let restored = |params: &[&str]| {
+            let mut nested = Nested::default();
+            params
+                .iter()
+                .map(|params| {
+                    let mut restored = String::new();
+                    outer
+                        .write_restored_after(params, &mut nested, &mut restored)
+                        .unwrap();
+                    restored
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(restored(&["0"]), ["\x1B[1;44;31m"]);
+        assert_eq!(restored(&[""]), ["\x1B[1;44;31m"]);
+        assert_eq!(restored(&["00;1"]), ["\x1B[1;44;31m"]);
+        assert_eq!(restored(&["0;32"]), ["\x1B[1;44m"]);
+        assert_eq!(restored(&["32", "39"]), ["", "\x1B[31m"]);
+        assert_eq!(restored(&["39", "49"]), ["", ""]);
+        assert_eq!(restored(&["2;42", "22;49"]), ["", "\x1B[1;44m"]);
+        assert_eq!(restored(&["3", "23"]), ["", ""]);
+        assert_eq!(restored(&["38;5;22", "39"]), ["", "\x1B[31m"]);
+        assert_eq!(restored(&["38;2;1;22;3", "22"]), ["", ""]);
+    }
+
+    #[test]
+    fn write_from() {
+        let attributes =
+            |string: &ColoredString| Attributes::new(string, string.fgcolor, string.bgcolor);
+        let codes = |previous: ColoredString, next: ColoredString| {
+            let mut codes = String::new();
+            let written = attributes(&next)
+                .write_from(&attributes(&previous), &mut codes)
+                .unwrap();
+            assert_eq!(written, !codes.is_empty());
+            codes
+        };
+        assert_eq!(codes("".into(), "".into()), "");
+        assert_eq!(codes("".into(), "".bold().red()), "\x1B[1;31m");
+        assert_eq!(codes("".bold().red(), "".into()), "\x1B[22;39m");
+        assert_eq!(
+            codes("".blink().strikethrough(), "".rapid_blink().on_green()),
+            "\x1B[25;29;6;42m"
+        );
+        assert_eq!(codes("".superscript(), "".subscript()), "\x1B[75;74m");
+        assert_eq!(codes("".red(), "".red().on_red()), "\x1B[41m");
+        assert_eq!(
+            codes("".into(), "".bright_red().on_bright_black()),
+            "\x1B[100;91m"
+        );
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn nested_strings_and_hyperlinks() {
+        control::with_override(true, || {
+            let nested = format!("x{}y", "z".blue()).red();
+            assert_eq!(
+                render(&["a".red(), nested.clone(), "b".red()]),
+                format!("\x1B[31ma\x1B[0m{nested}\x1B[31mb\x1B[0m")
+            );
+
+            let link = |text: &str| text.hyperlink("https://docs.rs");
+            let start = "\x1B]8;;https://docs.rs\x1B\\";
+            let rendered = control::with_hyperlinks(true, || {
+                render(&[link("a"), link("b").bold(), "c".into()])
+            });
+            assert_eq!(
+                rendered,
+                format!("{start}a\x1B[1mb{HYPERLINK_END}\x1B[22mc")
+            );
+        });
+    }
+}
@@ -0,0 +1,731 @@
+//! Loading of the terminal color schemes into a [`Palette`].
+//!
+//! The parsers only understand as much of each file format as color schemes
+//! use, which keeps `colored` free of YAML, TOML, JSON and plist
+//! dependencies.
+
+use crate::{css, Color, CustomColor, Palette, SchemeError, SchemeErrorKind};
+use std::{fs, path::Path};
+
+/// The format of a terminal color scheme file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemeFormat {
+    /// A base16 scheme, in YAML (`.yaml` or `.yml`).
+    Base16,
+    /// An iTerm2 color preset, in XML plist (`.itermcolors`).
+    ITerm,
+    /// The `[colors]` section of an Alacritty configuration, in TOML
+    /// (`.toml`).
+    Alacritty,
+    /// A Kitty theme or configuration (`.conf`).
+    Kitty,
+    /// A Windows Terminal color scheme, in JSON (`.json`).
+    WindowsTerminal,
+}
+
+impl SchemeFormat {
+    /// Guesses the format of a file from its extension.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "yaml" | "yml" => Some(Self::Base16),
+            "itermcolors" => Some(Self::ITerm),
+            "toml" => Some(Self::Alacritty),
+            "conf" => Some(Self::Kitty),
+            "json" => Some(Self::WindowsTerminal),
+            _ => None,
+        }
+    }
+}
+
+/// Loading of terminal color schemes.
+///
+/// Only the 16 standard colors are read from the schemes, the rest of the
+/// 256 color palette is filled as in [`Palette::new`].
+///
+/// ```rust
+/// use colored::{control, CustomColor, Palette, SchemeFormat};
+/// let kitty = (0..16)
+///     .map(|index| format!("color{index} #{:02x}0000\n", index * 16))
+///     .collect::<String>();
+/// let palette = Palette::from_scheme(&kitty, SchemeFormat::Kitty).unwrap();
+/// assert_eq!(palette.get(1), CustomColor::new(16, 0, 0));
+/// control::set_palette(palette);
+/// ```
+impl Palette {
+    /// Parses a color scheme in the given format.
+    ///
+    /// # Errors
+    /// Fails if the scheme is malformed, or if one of the 16 standard colors
+    /// is missing or invalid.
+    pub fn from_scheme(src: &str, format: SchemeFormat) -> Result<Self, SchemeError> {
+        let basic = match format {
+            SchemeFormat::Base16 => parse_base16(src)?,
+            SchemeFormat::ITerm => parse_iterm(src)?,
+            SchemeFormat::Alacritty => parse_alacritty(src)?,
+            SchemeFormat::Kitty => parse_kitty(src)?,
+            SchemeFormat::WindowsTerminal => parse_windows_terminal(src)?,
+        };
+        Ok(Self::new(basic))
+    }
+
+    /// Reads a color scheme file, guessing its format from its extension
+    /// with [`SchemeFormat::from_path`].
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read, if its format is unknown, or if it
+    /// cannot be parsed. The error names the file.
+    pub fn from_scheme_file(path: impl AsRef<Path>) -> Result<Self, SchemeError> {
+        let path = path.as_ref();
+        let load = || {
+            let format = SchemeFormat::from_path(path)
+                .ok_or_else(|| SchemeError::new(SchemeErrorKind::UnknownFormat))?;
+            let src = fs::read_to_string(path).map_err(SchemeError::io)?;
+            Self::from_scheme(&src, format)
+        };
+        load().map_err(|error| error.with_file(path))
+    }
+}
+
+/// Collects the 16 standard colors from the values of their keys.
+fn collect_colors<'a>(
+    keys: &[&str; 16],
+    mut value: impl FnMut(&str) -> Option<&'a str>,
+) -> Result<[CustomColor; 16], SchemeError> {
+    let mut colors = [CustomColor::new(0, 0, 0); 16];
+    for (color, key) in colors.iter_mut().zip(keys) {
+        let src = value(key).ok_or_else(|| SchemeError::missing_key(key))?;
+        *color = parse_color(src).ok_or_else(|| SchemeError::invalid_color(key, src))?;
+    }
+    Ok(colors)
+}
+
+/// Parses a `#rrggbb`, `0xrrggbb`, bare `rrggbb` or `#rgb` hex color, or a
+/// CSS color name.
+fn parse_color(src: &str) -> Option<CustomColor> {
+    let hex = src
+        .strip_prefix('#')
+        .or_else(|| src.strip_prefix("0x"))
+        .unwrap_or(src);
+    // only the `#rgb` notation is common for short colors
+    if hex.len() == 6 || (hex.len() == 3 && src.starts_with('#')) {
+        if let Ok(Color::TrueColor { r, g, b }) = format!("#{hex}").parse() {
+            return Some(CustomColor::new(r, g, b));
+        }
+    }
+    css::named_color(&src.to_ascii_lowercase()).map(CustomColor::from)
+}
+
+/// Removes the quotes around a YAML or TOML string, or the comment after an
+/// unquoted value.
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(rest) = value.strip_prefix(quote) {
+            return rest.split(quote).next().unwrap_or(rest);
+        }
+    }
+    value.split(" #").next().unwrap_or(value).trim_end()
+}
+
+/// Parses a base16 scheme, mapping its colors to the standard colors the way
+/// base16-shell does.
+///
+/// Both the original format, with the `baseXX` keys at the top level, and
+/// the newer one, with them under `palette`, are supported.
+fn parse_base16(src: &str) -> Result<[CustomColor; 16], SchemeError> {
+    let entries: Vec<(&str, &str)> = src
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim(), unquote(value)))
+        .collect();
+    let keys = [
+        "base00", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base05", "base03",
+        "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base07",
+    ];
+    collect_colors(&keys, |key| {
+        entries
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|&(_, value)| value)
+    })
+}
+
+/// Parses an iTerm2 color preset, in which each color is a dictionary of
+/// its components as real numbers between 0 and 1.
+fn parse_iterm(src: &str) -> Result<[CustomColor; 16], SchemeError> {
+    if !src.contains("<plist") {
+        return Err(SchemeError::new(SchemeErrorKind::InvalidSyntax));
+    }
+    let mut colors = [CustomColor::new(0, 0, 0); 16];
+    for (index, color) in colors.iter_mut().enumerate() {
+        let key = format!("Ansi {index} Color");
+        let dict = xml_value(src, &key, "dict").ok_or_else(|| SchemeError::missing_key(&key))?;
+        let mut components = [0; 3];
+        for (component, name) in components.iter_mut().zip(["Red", "Green", "Blue"]) {
+            let key = format!("{key}/{name} Component");
+            let name = format!("{name} Component");
+            let value = xml_value(dict, &name, "real")
+                .or_else(|| xml_value(dict, &name, "integer"))
+                .ok_or_else(|| SchemeError::missing_key(&key))?;
+            *component = match value.trim().parse::<f32>() {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                Ok(value) if (0.0..=1.0).contains(&value) => (value * 255.0).round() as u8,
+                _ => return Err(SchemeError::invalid_color(&key, value)),
+            };
+        }
+        let [r, g, b] = components;
+        *color = CustomColor::new(r, g, b);
+    }
+    Ok(colors)
+}
+
+/// Gets the content of the `<tag>` element following `<key>key</key>`.
+fn xml_value<'a>(src: &'a str, key: &str, tag: &str) -> Option<&'a str> {
+    let after_key = src.split_once(&format!("<key>{key}</key>"))?.1;
+    let value = after_key.trim_start().strip_prefix(&format!("<{tag}>"))?;
+    value
+        .split_once(&format!("</{tag}>"))
+        .map(|(value, _)| value)
+}
+
+/// Parses the `[colors.normal]` and `[colors.bright]` tables of an Alacritty
+/// configuration. The colors may also be given as dotted keys or as inline
+/// tables, e.g. `normal = { black = "#073642", ... }`, but not as inline
+/// tables nested in other inline tables.
+fn parse_alacritty(src: &str) -> Result<[CustomColor; 16], SchemeError> {
+    let mut table = "";
+    let mut entries: Vec<(String, &str)> = Vec::new();
+    for line in src.lines().map(str::trim) {
+        if line.starts_with("[[") || line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.split(" #").next().unwrap_or(header).trim_end();
+            table = header
+                .strip_suffix(']')
+                .ok_or_else(|| SchemeError::new(SchemeErrorKind::InvalidSyntax).with_key(line))?
+                .trim();
+        } else if let Some((key, value)) = line.split_once('=') {
+            let key = if table.is_empty() {
+                key.trim().to_owned()
+            } else {
+                format!("{table}.{}", key.trim())
+            };
+            if let Some(inline) = value.trim().strip_prefix('{') {
+                let (inline, _) = inline.split_once('}').ok_or_else(|| {
+                    SchemeError::new(SchemeErrorKind::InvalidSyntax).with_key(&key)
+                })?;
+                for (inner_key, value) in
+                    inline.split(',').filter_map(|entry| entry.split_once('='))
+                {
+                    entries.push((format!("{key}.{}", inner_key.trim()), unquote(value)));
+                }
+            } else {
+                entries.push((key, unquote(value)));
+            }
+        }
+    }
+    let keys = [
+        "colors.normal.black",
+        "colors.normal.red",
+        "colors.normal.green",
+        "colors.normal.yellow",
+        "colors.normal.blue",
+        "colors.normal.magenta",
+        "colors.normal.cyan",
+        "colors.normal.white",
+        "colors.bright.black",
+        "colors.bright.red",
+        "colors.bright.green",
+        "colors.bright.yellow",
+        "colors.bright.blue",
+        "colors.bright.magenta",
+        "colors.bright.cyan",
+        "colors.bright.white",
+    ];
+    collect_colors(&keys, |path| {
+        entries
+            .iter()
+            .find(|(key, _)| key == path)
+            .map(|&(_, value)| value)
+    })
+}
+
+/// Parses the `color0` to `color15` options of a Kitty configuration.
+fn parse_kitty(src: &str) -> Result<[CustomColor; 16], SchemeError> {
+    let entries: Vec<(&str, &str)> = src
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .map(|(key, value)| (key, value.trim()))
+        .collect();
+    let keys = [
+        "color0", "color1", "color2", "color3", "color4", "color5", "color6", "color7", "color8",
+        "color9", "color10", "color11", "color12", "color13", "color14", "color15",
+    ];
+    collect_colors(&keys, |key| {
+        // the last occurrence of an option wins
+        entries
+            .iter()
+            .rev()
+            .find(|&&(name, _)| name == key)
+            .map(|&(_, value)| value)
+    })
+}
+
+/// Parses a Windows Terminal color scheme, i.e. a JSON object with a string
+/// member for each color.
+fn parse_windows_terminal(src: &str) -> Result<[CustomColor; 16], SchemeError> {
+    let entries = json_object_strings(src)?;
+    let keys = [
+        "black",
+        "red",
+        "green",
+        "yellow",
+        "blue",
+        "purple",
+        "cyan",
+        "white",
+        "brightBlack",
+        "brightRed",
+        "brightGreen",
+        "brightYellow",
+        "brightBlue",
+        "brightPurple",
+        "brightCyan",
+        "brightWhite",
+    ];
+    collect_colors(&keys, |key| {
+        entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    })
+}
+
+/// Gets the members of a JSON object with their string values. The other
+/// values are only checked for balanced brackets, and read as empty strings.
+fn json_object_strings(src: &str) -> Result<Vec<(String, String)>, SchemeError> {
+    let syntax_error = || SchemeError::new(SchemeErrorKind::InvalidSyntax);
+    let mut chars = src.trim().chars().peekable();
+    let mut entries = Vec::new();
+    if chars.next() != Some('{') {
+        return Err(syntax_error());
+    }
+    skip_whitespace(&mut chars);
+    if chars.next_if_eq(&'}').is_some() {
+        return Ok(entries);
+    }
+    loop {
+        skip_whitespace(&mut chars);
+        if chars.next() != Some('"') {
+            return Err(syntax_error());
+        }
+        let key = json_string(&mut chars).ok_or_else(syntax_error)?;
+        skip_whitespace(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(syntax_error().with_key(&key));
+        }
+        skip_whitespace(&mut chars);
+        let value = if chars.next_if_eq(&'"').is_some() {
+            json_string(&mut chars)
+        } else {
+            skip_json_value(&mut chars).map(|()| String::new())
+        };
+        let value = value.ok_or_else(|| syntax_error().with_key(&key))?;
+        skip_whitespace(&mut chars);
+        let end = chars.next();
+        if !matches!(end, Some(',' | '}')) {
+            return Err(syntax_error().with_key(&key));
+        }
+        entries.push((key, value));
+        if end == Some('}') {
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+fn skip_whitespace(chars: &mut Chars<'_>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+/// Reads a JSON string whose opening quote was already consumed.
+fn json_string(chars: &mut Chars<'_>) -> Option<String> {
+    let mut string = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(string),
+            '\\' => match chars.next()? {
+                'n' => string.push('\n'),
+                't' => string.push('\t'),
+                'r' => string.push('\r'),
+                'b' => string.push('\u{8}'),
+                'f' => string.push('\u{c}'),
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    let code = u32::from_str_radix(&code, 16).ok()?;
+                    string.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                c => string.push(c),
+            },
+            c => string.push(c),
+        }
+    }
+}
+
+/// Skips a JSON value that is not a string, up to the `,` or `}` following
+/// it.
+fn skip_json_value(chars: &mut Chars<'_>) -> Option<()> {
+    let mut depth = 0_usize;
+    loop {
+        match chars.peek()? {
+            ',' | '}' if depth == 0 => return Some(()),
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth = depth.checked_sub(1)?,
+            '"' => {
+                chars.next();
+                json_string(chars)?;
+                continue;
+            }
+            _ => {}
+        }
+        chars.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    const BLUE: CustomColor = CustomColor::new(0x26, 0x8b, 0xd2);
+    const BRIGHT_WHITE: CustomColor = CustomColor::new(0xfd, 0xf6, 0xe3);
+
+    fn assert_colors(palette: &Palette) {
+        assert_eq!(palette.get(4), BLUE);
+        assert_eq!(palette.get(12), BLUE);
+        assert_eq!(palette.get(15), BRIGHT_WHITE);
+        assert_eq!(palette.get(208), CustomColor::new(255, 135, 0));
+    }
+
+    const BASE16: &str = r#"
+scheme: "Solarized Dark"
+author: "Ethan Schoonover"
+base00: "002b36" # background
+base01: "073642"
+base02: "586e75"
+base03: "657b83"
+base04: "839496"
+base05: "93a1a1"
+base06: "eee8d5"
+base07: "fdf6e3"
+base08: "dc322f"
+base09: "cb4b16"
+base0A: "b58900"
+base0B: "859900"
+base0C: "2aa198"
+base0D: "268bd2"
+base0E: "6c71c4"
+base0F: "d33682"
+"#;
+
+    #[test]
+    fn base16() {
+        let palette = Palette::from_scheme(BASE16, SchemeFormat::Base16).unwrap();
+        assert_colors(&palette);
+        assert_eq!(palette.get(0), CustomColor::new(0x00, 0x2b, 0x36));
+        assert_eq!(palette.get(8), CustomColor::new(0x65, 0x7b, 0x83));
+    }
+
+    #[test]
+    fn base16_palette_section() {
+        let src = BASE16
+            .replace("\nbase", "\n  base")
+            .replace("author:", "palette:\nauthor:")
+            .replace("\"dc322f\"", "'#dc322f'");
+        let palette = Palette::from_scheme(&src, SchemeFormat::Base16).unwrap();
+        assert_colors(&palette);
+        assert_eq!(palette.get(1), CustomColor::new(0xdc, 0x32, 0x2f));
+    }
+
+    #[test]
+    fn base16_errors() {
+        let src = BASE16.replace("base0D: \"268bd2\"", "");
+        let error = Palette::from_scheme(&src, SchemeFormat::Base16).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::MissingKey);
+        assert_eq!(error.key(), Some("base0D"));
+
+        let src = BASE16.replace("268bd2", "268bzz");
+        let error = Palette::from_scheme(&src, SchemeFormat::Base16).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::InvalidColor);
+        assert_eq!(
+            error.to_string(),
+            r#"invalid color "268bzz" for key "base0D""#
+        );
+    }
+
+    fn iterm(blue: &str) -> String {
+        let mut src = String::from("<?xml version=\"1.0\"?>\n<plist version=\"1.0\">\n<dict>\n");
+        for index in 0..16 {
+            let (r, g, b) = match index {
+                4 | 12 => ("0.14901961", "0.54509804", blue),
+                15 => ("0.99215686", "0.96470588", "0.89019608"),
+                _ => ("0", "0", "0"),
+            };
+            write!(
+                src,
+                "\t<key>Ansi {index} Color</key>\n\t<dict>\n\
+                 \t\t<key>Alpha Component</key>\n\t\t<real>1</real>\n\
+                 \t\t<key>Blue Component</key>\n\t\t<real>{b}</real>\n\
+                 \t\t<key>Color Space</key>\n\t\t<string>sRGB</string>\n\
+                 \t\t<key>Green Component</key>\n\t\t<real>{g}</real>\n\
+                 \t\t<key>Red Component</key>\n\t\t<real>{r}</real>\n\
+                 \t</dict>\n"
+            )
+            .unwrap();
+        }
+        src + "</dict>\n</plist>\n"
+    }
+
+    #[test]
+    fn iterm_colors() {
+        let palette = Palette::from_scheme(&iterm("0.82352941"), SchemeFormat::ITerm).unwrap();
+        assert_colors(&palette);
+    }
+
+    #[test]
+    fn iterm_errors() {
+        let error = Palette::from_scheme(&iterm("1.5"), SchemeFormat::ITerm).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::InvalidColor);
+        assert_eq!(error.key(), Some("Ansi 4 Color/Blue Component"));
+
+        let src = iterm("0.82352941").replace("Ansi 7 Color", "Ansi 7 Colour");
+        let error = Palette::from_scheme(&src, SchemeFormat::ITerm).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::MissingKey);
+        assert_eq!(error.key(), Some("Ansi 7 Color"));
+
+        let error = Palette::from_scheme("{}", SchemeFormat::ITerm).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::InvalidSyntax);
+    }
+
+    const ALACRITTY: &str = r##"
+[window]
+opacity = 0.9
+
+[colors.primary]
+background = "#002b36"
+
+[colors.normal]
+black = "#073642"
+red = "#dc322f"
+green = "#859900"
+yellow = "#b58900"
+blue = "#268bd2"
+magenta = "#d33682"
+cyan = "#2aa198"
+white = "#eee8d5"
+
+[colors.bright] # comment
+black = '0x002b36'
+red = "#cb4b16"
+green = "#586e75"
+yellow = "#657b83"
+blue = "#268bd2"
+magenta = "#6c71c4"
+cyan = "#93a1a1"
+white = "#fdf6e3" # comment
+"##;
+
+    #[test]
+    fn alacritty() {
+        let palette = Palette::from_scheme(ALACRITTY, SchemeFormat::Alacritty).unwrap();
+        assert_colors(&palette);
+        assert_eq!(palette.get(8), CustomColor::new(0x00, 0x2b, 0x36));
+    }
+
+    #[test]
+    fn alacritty_dotted_keys() {
+        let mut table = "";
+        let mut src = String::new();
+        for line in ALACRITTY.lines() {
+            if let Some(header) = line.strip_prefix('[') {
+                table = header.split(']').next().unwrap();
+            } else if !line.is_empty() {
+                writeln!(src, "{table}.{line}").unwrap();
+            }
+        }
+        let palette = Palette::from_scheme(&src, SchemeFormat::Alacritty).unwrap();
+        assert_colors(&palette);
+    }
+
+    #[test]
+    fn alacritty_inline_tables() {
+        let src = r##"
+[colors]
+primary = { background = "#002b36" }
+normal = { black = "#073642", red = "#dc322f", green = "#859900", yellow = "#b58900", blue = "#268bd2", magenta = "#d33682", cyan = "#2aa198", white = "#eee8d5" }
+bright = { black = '0x002b36', red = "#cb4b16", green = "#586e75", yellow = "#657b83", blue = "#268bd2", magenta = "#6c71c4", cyan = "#93a1a1", white = "#fdf6e3" } # comment
+"##;
+        let palette = Palette::from_scheme(src, SchemeFormat::Alacritty).unwrap();
+        assert_colors(&palette);
+
+        let error = Palette::from_scheme(
+            "[colors]\nnormal = { black = \"#000000\"",
+            SchemeFormat::Alacritty,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::InvalidSyntax);
+        assert_eq!(error.key(), Some("colors.normal"));
+    }
+
+    #[test]
+    fn alacritty_errors() {
+        let src = ALACRITTY.replace("[colors.normal]", "[colors.normal");
+        let error = Palette::from_scheme(&src, SchemeFormat::Alacritty).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::InvalidSyntax);
+
+        let src = ALACRITTY.replace("magenta = \"#d33682\"", "");
+        let error = Palette::from_scheme(&src, SchemeFormat::Alacritty).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::MissingKey);
+        assert_eq!(error.key(), Some("colors.normal.magenta"));
+    }
+
+    const KITTY: &str = "
+# Solarized
+foreground #839496
+color0  #073642
+color1  #dc322f
+color2  #859900
+color3  #b58900
+color4  #000000
+color5  #d33682
+color6  #2aa198
+color7  #eee8d5
+color8  #002b36
+color9  #cb4b16
+color10 #586e75
+color11 #657b83
+color12 #268bd2
+color13 #6c71c4
+color14 #93a1a1
+color15 #fdf6e3
+color4  #268bd2
+";
+
+    #[test]
+    fn kitty() {
+        let palette = Palette::from_scheme(KITTY, SchemeFormat::Kitty).unwrap();
+        assert_colors(&palette);
+    }
+
+    #[test]
+    fn kitty_color_names() {
+        let src = KITTY.replace("color1  #dc322f", "color1  Red");
+        let palette = Palette::from_scheme(&src, SchemeFormat::Kitty).unwrap();
+        assert_eq!(palette.get(1), CustomColor::new(255, 0, 0));
+    }
+
+    #[test]
+    fn kitty_errors() {
+        let src = KITTY.replace("color15 #fdf6e3", "color15 #fdf6e");
+        let error = Palette::from_scheme(&src, SchemeFormat::Kitty).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::InvalidColor);
+        assert_eq!(error.key(), Some("color15"));
+    }
+
+    const WINDOWS_TERMINAL: &str = r##"{
+    "name": "Solarized \"Dark\"",
+    "cursorColor": "#FFFFFF",
+    "selectionBackground": "#FFFFFF",
+    "opacity": 90,
+    "extra": { "nested": ["a", "}"] },
+    "black": "#073642",
+    "red": "#DC322F",
+    "green": "#859900",
+    "yellow": "#B58900",
+    "blue": "#268BD2",
+    "purple": "#D33682",
+    "cyan": "#2AA198",
+    "white": "#EEE8D5",
+    "brightBlack": "#002B36",
+    "brightRed": "#CB4B16",
+    "brightGreen": "#586E75",
+    "brightYellow": "#657B83",
+    "brightBlue": "#268BD2",
+    "brightPurple": "#6C71C4",
+    "brightCyan": "#93A1A1",
+    "brightWhite": "#FDF6E3"
+}"##;
+
+    #[test]
+    fn windows_terminal() {
+        let palette =
+            Palette::from_scheme(WINDOWS_TERMINAL, SchemeFormat::WindowsTerminal).unwrap();
+        assert_colors(&palette);
+    }
+
+    #[test]
+    fn windows_terminal_errors() {
+        let src = WINDOWS_TERMINAL.replace("\"purple\"", "\"magenta\"");
+        let error = Palette::from_scheme(&src, SchemeFormat::WindowsTerminal).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::MissingKey);
+        assert_eq!(error.key(), Some("purple"));
+
+        for src in [
+            "",
+            "[]",
+            "{\"black\" \"#000000\"}",
+            "{\"black\": \"#000000\"",
+        ] {
+            let error = Palette::from_scheme(src, SchemeFormat::WindowsTerminal).unwrap_err();
+            assert_eq!(error.kind(), SchemeErrorKind::InvalidSyntax, "{src}");
+        }
+    }
+
+    #[test]
+    fn formats_from_path() {
+        let format = |path: &str| SchemeFormat::from_path(Path::new(path));
+        assert_eq!(format("solarized.yaml"), Some(SchemeFormat::Base16));
+        assert_eq!(format("solarized.YML"), Some(SchemeFormat::Base16));
+        assert_eq!(format("Solarized.itermcolors"), Some(SchemeFormat::ITerm));
+        assert_eq!(format("alacritty.toml"), Some(SchemeFormat::Alacritty));
+        assert_eq!(format("kitty.conf"), Some(SchemeFormat::Kitty));
+        assert_eq!(
+            format("solarized.json"),
+            Some(SchemeFormat::WindowsTerminal)
+        );
+        assert_eq!(format("solarized"), None);
+        assert_eq!(format("solarized.txt"), None);
+    }
+
+    #[test]
+    fn files() {
+        let path = std::env::temp_dir().join(format!("colored-{}.conf", std::process::id()));
+        fs::write(&path, KITTY).unwrap();
+        let palette = Palette::from_scheme_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_colors(&palette.unwrap());
+
+        let error = Palette::from_scheme_file(&path).unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::Io);
+        assert_eq!(error.file(), Some(path.as_path()));
+        assert!(std::error::Error::source(&error).is_some());
+        let message = format!("{}: cannot read color scheme", path.display());
+        assert_eq!(error.to_string(), message);
+
+        let error = Palette::from_scheme_file("solarized.txt").unwrap_err();
+        assert_eq!(error.kind(), SchemeErrorKind::UnknownFormat);
+        assert_eq!(
+            error.to_string(),
+            "solarized.txt: unknown color scheme format"
+        );
+    }
+}
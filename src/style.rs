@@ -51,17 +51,24 @@ macro_rules! impl_assign_op_trait {
     };
 }
 
-const CLEARV: u8 = 0b0000_0000;
-const BOLD: u8 = 0b0000_0001;
-const UNDERLINE: u8 = 0b0000_0010;
-const REVERSED: u8 = 0b0000_0100;
-const ITALIC: u8 = 0b0000_1000;
-const BLINK: u8 = 0b0001_0000;
-const HIDDEN: u8 = 0b0010_0000;
-const DIMMED: u8 = 0b0100_0000;
-const STRIKETHROUGH: u8 = 0b1000_0000;
-
-static STYLES: [(u8, Styles); 8] = [
+const CLEARV: u16 = 0b0000_0000_0000_0000;
+const BOLD: u16 = 0b0000_0000_0000_0001;
+const UNDERLINE: u16 = 0b0000_0000_0000_0010;
+const REVERSED: u16 = 0b0000_0000_0000_0100;
+const ITALIC: u16 = 0b0000_0000_0000_1000;
+const BLINK: u16 = 0b0000_0000_0001_0000;
+const HIDDEN: u16 = 0b0000_0000_0010_0000;
+const DIMMED: u16 = 0b0000_0000_0100_0000;
+const STRIKETHROUGH: u16 = 0b0000_0000_1000_0000;
+const OVERLINE: u16 = 0b0000_0001_0000_0000;
+const DOUBLE_UNDERLINE: u16 = 0b0000_0010_0000_0000;
+const RAPID_BLINK: u16 = 0b0000_0100_0000_0000;
+const FRAMED: u16 = 0b0000_1000_0000_0000;
+const ENCIRCLED: u16 = 0b0001_0000_0000_0000;
+const SUPERSCRIPT: u16 = 0b0010_0000_0000_0000;
+const SUBSCRIPT: u16 = 0b0100_0000_0000_0000;
+
+static STYLES: [(u16, Styles); 15] = [
     (BOLD, Styles::Bold),
     (DIMMED, Styles::Dimmed),
     (UNDERLINE, Styles::Underline),
@@ -70,6 +77,27 @@ static STYLES: [(u8, Styles); 8] = [
     (BLINK, Styles::Blink),
     (HIDDEN, Styles::Hidden),
     (STRIKETHROUGH, Styles::Strikethrough),
+    (OVERLINE, Styles::Overline),
+    (DOUBLE_UNDERLINE, Styles::DoubleUnderline),
+    (RAPID_BLINK, Styles::RapidBlink),
+    (FRAMED, Styles::Framed),
+    (ENCIRCLED, Styles::Encircled),
+    (SUPERSCRIPT, Styles::Superscript),
+    (SUBSCRIPT, Styles::Subscript),
+];
+
+/// The SGR codes turning styles off, with the styles they turn off.
+const OFF_CODES: [(u16, u8); 10] = [
+    (BOLD | DIMMED, 22),
+    (ITALIC, 23),
+    (UNDERLINE | DOUBLE_UNDERLINE, 24),
+    (BLINK | RAPID_BLINK, 25),
+    (REVERSED, 27),
+    (HIDDEN, 28),
+    (STRIKETHROUGH, 29),
+    (FRAMED | ENCIRCLED, 54),
+    (OVERLINE, 55),
+    (SUPERSCRIPT | SUBSCRIPT, 75),
 ];
 
 pub static CLEAR: Style = Style(CLEARV);
@@ -155,7 +183,7 @@ pub static CLEAR: Style = Style(CLEARV);
 /// assert!(very_loud_style.contains(Styles::Bold));
 /// ```
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub struct Style(u8);
+pub struct Style(u16);
 
 /// Enum containing all of the available style settings that can be
 /// applied to a [`Styles`] and by extension, a colrized type.
@@ -204,10 +232,71 @@ pub enum Styles {
     Blink,
     Hidden,
     Strikethrough,
+    Overline,
+    DoubleUnderline,
+    RapidBlink,
+    Framed,
+    Encircled,
+    Superscript,
+    Subscript,
+}
+
+/// The shape of the underline drawn by [`Styles::Underline`].
+///
+/// Only some terminals (kitty, `WezTerm`, iTerm2, VTE based terminals,
+/// Windows Terminal, ...) support the other shapes, the other ones draw a
+/// single underline instead.
+///
+/// ```rust
+/// # use colored::*;
+/// let typo = "recieve".underline_style(UnderlineStyle::Curly).underline_color(Color::Red);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnderlineStyle {
+    /// A single straight line.
+    #[default]
+    Single,
+    /// Two straight lines.
+    Double,
+    /// A wavy line, as used for spell checking.
+    Curly,
+    /// A dotted line.
+    Dotted,
+    /// A dashed line.
+    Dashed,
+}
+
+impl UnderlineStyle {
+    pub(crate) const fn to_str(self) -> &'static str {
+        match self {
+            Self::Single => "4",
+            Self::Double => "4:2",
+            Self::Curly => "4:3",
+            Self::Dotted => "4:4",
+            Self::Dashed => "4:5",
+        }
+    }
+}
+
+/// Checks if the terminal supports the underline shapes and colors (SGR
+/// `4:x` and `58`), from the environment variables read by `var`. Other
+/// terminals may misread them, e.g. `58;2;r;g;b` as dimmed.
+pub fn detect_extended_underlines(var: impl Fn(&str) -> Option<String>) -> bool {
+    let term = var("TERM").unwrap_or_default();
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    let vte_version = var("VTE_VERSION").and_then(|version| version.parse::<u32>().ok());
+    ["kitty", "wezterm", "alacritty", "foot"]
+        .iter()
+        .any(|name| term.contains(name))
+        || ["WezTerm", "iTerm.app", "vscode"].contains(&program.as_str())
+        || var("KITTY_WINDOW_ID").is_some()
+        || var("WT_SESSION").is_some()
+        || vte_version.is_some_and(|version| version >= 5102)
 }
 
 impl Styles {
-    fn to_str<'a>(self) -> &'a str {
+    const fn to_str(self) -> &'static str {
         match self {
             Self::Clear => "", // unreachable, but we don't want to panic
             Self::Bold => "1",
@@ -218,10 +307,17 @@ impl Styles {
             Self::Reversed => "7",
             Self::Hidden => "8",
             Self::Strikethrough => "9",
+            Self::DoubleUnderline => "21",
+            Self::Framed => "51",
+            Self::Encircled => "52",
+            Self::Overline => "53",
+            Self::Superscript => "73",
+            Self::Subscript => "74",
+            Self::RapidBlink => "6",
         }
     }
 
-    fn to_u8(self) -> u8 {
+    fn to_u16(self) -> u16 {
         match self {
             Self::Clear => CLEARV,
             Self::Bold => BOLD,
@@ -232,17 +328,25 @@ impl Styles {
             Self::Reversed => REVERSED,
             Self::Hidden => HIDDEN,
             Self::Strikethrough => STRIKETHROUGH,
+            Self::Overline => OVERLINE,
+            Self::DoubleUnderline => DOUBLE_UNDERLINE,
+            Self::RapidBlink => RAPID_BLINK,
+            Self::Framed => FRAMED,
+            Self::Encircled => ENCIRCLED,
+            Self::Superscript => SUPERSCRIPT,
+            Self::Subscript => SUBSCRIPT,
         }
     }
 
-    fn from_u8(u: u8) -> Option<Vec<Self>> {
+    #[cfg(test)]
+    fn from_u16(u: u16) -> Option<Vec<Self>> {
         if u == CLEARV {
             return None;
         }
 
         let res: Vec<Self> = STYLES
             .iter()
-            .filter(|&(mask, _)| (0 != (u & mask)))
+            .filter(|&(mask, _)| 0 != (u & mask))
             .map(|&(_, value)| value)
             .collect();
         if res.is_empty() {
@@ -257,7 +361,7 @@ impl BitAnd<Self> for Styles {
     type Output = Style;
 
     fn bitand(self, rhs: Self) -> Self::Output {
-        Style(self.to_u8() & rhs.to_u8())
+        Style(self.to_u16() & rhs.to_u16())
     }
 }
 
@@ -267,7 +371,7 @@ impl BitAnd<Style> for Styles {
     type Output = Style;
 
     fn bitand(self, rhs: Style) -> Self::Output {
-        Style(self.to_u8() & rhs.0)
+        Style(self.to_u16() & rhs.0)
     }
 }
 
@@ -277,7 +381,7 @@ impl BitOr<Self> for Styles {
     type Output = Style;
 
     fn bitor(self, rhs: Self) -> Self::Output {
-        Style(self.to_u8() | rhs.to_u8())
+        Style(self.to_u16() | rhs.to_u16())
     }
 }
 
@@ -287,7 +391,7 @@ impl BitOr<Style> for Styles {
     type Output = Style;
 
     fn bitor(self, rhs: Style) -> Self::Output {
-        Style(self.to_u8() | rhs.0)
+        Style(self.to_u16() | rhs.0)
     }
 }
 
@@ -297,7 +401,7 @@ impl BitXor<Self> for Styles {
     type Output = Style;
 
     fn bitxor(self, rhs: Self) -> Self::Output {
-        Style(self.to_u8() ^ rhs.to_u8())
+        Style(self.to_u16() ^ rhs.to_u16())
     }
 }
 
@@ -307,7 +411,7 @@ impl BitXor<Style> for Styles {
     type Output = Style;
 
     fn bitxor(self, rhs: Style) -> Self::Output {
-        Style(self.to_u8() ^ rhs.0)
+        Style(self.to_u16() ^ rhs.0)
     }
 }
 
@@ -317,7 +421,7 @@ impl Not for Styles {
     type Output = Style;
 
     fn not(self) -> Self::Output {
-        Style(!self.to_u8())
+        Style(!self.to_u16())
     }
 }
 
@@ -325,7 +429,7 @@ impl Not for &Styles {
     type Output = Style;
 
     fn not(self) -> Self::Output {
-        Style(!self.to_u8())
+        Style(!self.to_u16())
     }
 }
 
@@ -341,17 +445,45 @@ impl Style {
     /// ```
     #[must_use]
     pub fn contains(self, style: Styles) -> bool {
-        let s = style.to_u8();
+        let s = style.to_u16();
         self.0 & s == s
     }
 
-    pub(crate) fn to_str(self) -> String {
-        let styles = Styles::from_u8(self.0).unwrap_or_default();
-        styles
+    /// The SGR codes setting the styles, e.g. `1` for bold.
+    pub(crate) fn codes(self) -> impl Iterator<Item = &'static str> {
+        STYLES
+            .iter()
+            .filter(move |&(mask, _)| self.0 & mask != 0)
+            .map(|&(_, styles)| styles.to_str())
+    }
+
+    /// The SGR codes turning off the styles of `self` that `next` doesn't
+    /// have. Some codes turn off two styles, e.g. `22` both bold and dimmed,
+    /// so the styles still on after them may lack some of `next`.
+    pub(crate) fn off_codes(self, next: Self) -> impl Iterator<Item = u8> {
+        OFF_CODES
+            .into_iter()
+            .filter(move |&(mask, _)| self.0 & !next.0 & mask != 0)
+            .map(|(_, code)| code)
+    }
+
+    /// The style set by the SGR `code`, e.g. bold for `1`.
+    pub(crate) fn set_by(code: u8) -> Self {
+        let style = STYLES
             .iter()
-            .map(|s| s.to_str())
-            .collect::<Vec<&str>>()
-            .join(";")
+            .find_map(|&(style, styles)| (styles.to_str().parse() == Ok(code)).then_some(style))
+            .unwrap_or(CLEARV);
+        Self(style)
+    }
+
+    /// The styles of `self` turned off by the SGR `code`, e.g. bold and
+    /// dimmed for `22`.
+    pub(crate) fn turned_off_by(self, code: u8) -> Self {
+        let mask = OFF_CODES
+            .into_iter()
+            .find_map(|(mask, off)| (off == code).then_some(mask))
+            .unwrap_or(CLEARV);
+        Self(self.0 & mask)
     }
 
     /// Adds the `two` style switch to this Style.
@@ -369,7 +501,7 @@ impl Style {
     /// assert_eq!(cstr2.fgcolor, Some(Color::Blue));
     /// ```
     pub fn add(&mut self, two: Styles) {
-        self.0 |= two.to_u8();
+        self.0 |= two.to_u16();
     }
 
     /// Turns off a style switch.
@@ -386,7 +518,7 @@ impl Style {
     /// assert_eq!(cstr2.fgcolor, Some(Color::Blue));
     /// ```
     pub fn remove(&mut self, two: Styles) {
-        self.0 &= !two.to_u8();
+        self.0 &= !two.to_u16();
     }
 
     /// Makes this `Style` include Bold.
@@ -444,6 +576,55 @@ impl Style {
         self.add(Styles::Strikethrough);
         self
     }
+
+    /// Makes this `Style` include Overline.
+    #[must_use]
+    pub fn overline(mut self) -> Self {
+        self.add(Styles::Overline);
+        self
+    }
+
+    /// Makes this `Style` include `DoubleUnderline`.
+    #[must_use]
+    pub fn double_underline(mut self) -> Self {
+        self.add(Styles::DoubleUnderline);
+        self
+    }
+
+    /// Makes this `Style` include `RapidBlink`.
+    #[must_use]
+    pub fn rapid_blink(mut self) -> Self {
+        self.add(Styles::RapidBlink);
+        self
+    }
+
+    /// Makes this `Style` include Framed.
+    #[must_use]
+    pub fn framed(mut self) -> Self {
+        self.add(Styles::Framed);
+        self
+    }
+
+    /// Makes this `Style` include Encircled.
+    #[must_use]
+    pub fn encircled(mut self) -> Self {
+        self.add(Styles::Encircled);
+        self
+    }
+
+    /// Makes this `Style` include Superscript.
+    #[must_use]
+    pub fn superscript(mut self) -> Self {
+        self.add(Styles::Superscript);
+        self
+    }
+
+    /// Makes this `Style` include Subscript.
+    #[must_use]
+    pub fn subscript(mut self) -> Self {
+        self.add(Styles::Subscript);
+        self
+    }
 }
 
 impl BitAnd<Self> for Style {
@@ -460,7 +641,7 @@ impl BitAnd<Styles> for Style {
     type Output = Self;
 
     fn bitand(self, rhs: Styles) -> Self::Output {
-        Self(self.0 & rhs.to_u8())
+        Self(self.0 & rhs.to_u16())
     }
 }
 
@@ -480,7 +661,7 @@ impl BitOr<Styles> for Style {
     type Output = Self;
 
     fn bitor(self, rhs: Styles) -> Self::Output {
-        Self(self.0 | rhs.to_u8())
+        Self(self.0 | rhs.to_u16())
     }
 }
 
@@ -500,7 +681,7 @@ impl BitXor<Styles> for Style {
     type Output = Self;
 
     fn bitxor(self, rhs: Styles) -> Self::Output {
-        Self(self.0 ^ rhs.to_u8())
+        Self(self.0 ^ rhs.to_u16())
     }
 }
 
@@ -542,13 +723,13 @@ impl Default for Style {
 
 impl From<Styles> for Style {
     fn from(value: Styles) -> Self {
-        Self(value.to_u8())
+        Self(value.to_u16())
     }
 }
 
 impl From<&Styles> for Style {
     fn from(value: &Styles) -> Self {
-        Self(value.to_u8())
+        Self(value.to_u16())
     }
 }
 
@@ -566,42 +747,43 @@ impl FromIterator<Styles> for Style {
 mod tests {
     use super::*;
 
-    mod u8_to_styles_invalid_is_none {
+    mod u16_to_styles_invalid_is_none {
         use super::super::Styles;
         use super::super::CLEARV;
 
         #[test]
         fn empty_is_none() {
-            assert_eq!(None, Styles::from_u8(CLEARV));
+            assert_eq!(None, Styles::from_u16(CLEARV));
         }
     }
 
-    mod u8_to_styles_isomorphism {
+    mod u16_to_styles_isomorphism {
         use super::super::Styles;
         use super::super::{
-            BLINK, BOLD, DIMMED, HIDDEN, ITALIC, REVERSED, STRIKETHROUGH, UNDERLINE,
+            BLINK, BOLD, DIMMED, DOUBLE_UNDERLINE, ENCIRCLED, FRAMED, HIDDEN, ITALIC, OVERLINE,
+            RAPID_BLINK, REVERSED, STRIKETHROUGH, SUBSCRIPT, SUPERSCRIPT, UNDERLINE,
         };
 
         macro_rules! value_isomorph {
             ($name:ident, $value:expr) => {
                 #[test]
                 fn $name() {
-                    let u = Styles::from_u8($value);
+                    let u = Styles::from_u16($value);
                     assert!(
                         u.is_some(),
-                        "{}: Styles::from_u8 -> None",
+                        "{}: Styles::from_u16 -> None",
                         stringify!($value)
                     );
                     let u = u.unwrap();
                     assert!(
                         u.len() == 1,
-                        "{}: Styles::from_u8 found {} styles (expected 1)",
+                        "{}: Styles::from_u16 found {} styles (expected 1)",
                         stringify!($value),
                         u.len()
                     );
                     assert!(
-                        u[0].to_u8() == $value,
-                        "{}: to_u8() doesn't match its const value",
+                        u[0].to_u16() == $value,
+                        "{}: to_u16() doesn't match its const value",
                         stringify!($value)
                     );
                 }
@@ -616,6 +798,13 @@ mod tests {
         value_isomorph!(hidden, HIDDEN);
         value_isomorph!(dimmed, DIMMED);
         value_isomorph!(strikethrough, STRIKETHROUGH);
+        value_isomorph!(overline, OVERLINE);
+        value_isomorph!(double_underline, DOUBLE_UNDERLINE);
+        value_isomorph!(rapid_blink, RAPID_BLINK);
+        value_isomorph!(framed, FRAMED);
+        value_isomorph!(encircled, ENCIRCLED);
+        value_isomorph!(superscript, SUPERSCRIPT);
+        value_isomorph!(subscript, SUBSCRIPT);
     }
 
     mod styles_combine_complex {
@@ -623,9 +812,9 @@ mod tests {
         use super::super::{Style, Styles};
 
         fn style_from_multiples(styles: &[Styles]) -> Style {
-            let mut res = Style(styles[0].to_u8());
+            let mut res = Style(styles[0].to_u16());
             for s in &styles[1..] {
-                res = Style(res.0 | s.to_u8());
+                res = Style(res.0 | s.to_u16());
             }
             res
         }
@@ -633,7 +822,7 @@ mod tests {
         macro_rules! test_aggreg {
             ($styles:expr, $expect:expr) => {{
                 let v = style_from_multiples($styles);
-                let r = Styles::from_u8(v.0).expect("should find styles");
+                let r = Styles::from_u16(v.0).expect("should find styles");
                 assert_eq!(&$expect as &[Styles], &r[..])
             }};
         }
@@ -659,7 +848,7 @@ mod tests {
         macro_rules! test_combine {
             ($styles:expr) => {{
                 let v = style_from_multiples($styles);
-                let r = Styles::from_u8(v.0).expect("should find styles");
+                let r = Styles::from_u16(v.0).expect("should find styles");
                 assert_eq!($styles, &r[..])
             }};
         }
@@ -723,14 +912,27 @@ mod tests {
                 Blink,
                 Hidden,
                 Strikethrough,
+                Overline,
+                DoubleUnderline,
+                RapidBlink,
+                Framed,
+                Encircled,
+                Superscript,
+                Subscript,
             ];
             test_combine!(s);
         }
+
+        #[test]
+        fn beyond_eight_bits() {
+            let s: &[Styles] = &[Bold, Strikethrough, Overline, Subscript];
+            test_combine!(s);
+        }
     }
 
     #[test]
     fn test_style_contains() {
-        let mut style = Style(Styles::Bold.to_u8());
+        let mut style = Style(Styles::Bold.to_u16());
         style.add(Styles::Italic);
 
         assert!(style.contains(Styles::Bold));
@@ -758,7 +960,7 @@ mod tests {
         }
 
         /// TTABLE = `TRUTH_TABLE`
-        const TTABLE: (u8, u8) = (0b0101, 0b0011);
+        const TTABLE: (u16, u16) = (0b0101, 0b0011);
 
         #[test]
         fn binops() {
@@ -802,7 +1004,8 @@ mod tests {
             let not_bold = !Style(BOLD);
             assert!(!not_bold.contains(Styles::Bold));
             assert!(not_bold.contains(Styles::Strikethrough));
-            assert_eq!(!Style(0b0011_0101), Style(0b1100_1010));
+            assert_eq!(!Style(0b0011_0101), Style(0b1111_1111_1100_1010));
+            assert_eq!(!Style(BOLD) & Style(SUBSCRIPT), Style(SUBSCRIPT));
         }
 
         #[test]
@@ -876,4 +1079,23 @@ mod tests {
             assert_eq!(not_bold, Style(!BOLD));
         }
     }
+
+    #[test]
+    fn extended_underlines_detection() {
+        let detect = |vars: &[(&str, &str)]| {
+            detect_extended_underlines(|name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| (*value).to_string())
+            })
+        };
+        assert!(!detect(&[]));
+        assert!(!detect(&[("TERM", "xterm-256color")]));
+        assert!(!detect(&[("VTE_VERSION", "5000")]));
+        assert!(detect(&[("TERM", "xterm-kitty")]));
+        assert!(detect(&[("TERM_PROGRAM", "WezTerm")]));
+        assert!(detect(&[("KITTY_WINDOW_ID", "1")]));
+        assert!(detect(&[("WT_SESSION", "")]));
+        assert!(detect(&[("VTE_VERSION", "6003")]));
+    }
 }
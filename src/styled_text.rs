@@ -0,0 +1,371 @@
+use crate::{write_padded, ColoredString, Renderer};
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Bound, RangeBounds},
+};
+
+/// A text made of several [`ColoredString`] spans, each with its own colors
+/// and style, e.g. a line of a compiler diagnostic.
+///
+/// The spans stay inspectable and can still be changed after the text is
+/// built. The lengths and byte offsets of the methods are those of the text
+/// of the spans, without escape sequences.
+///
+/// ```rust
+/// # use colored::*;
+/// let mut line = "error".red().bold() + "[E0308]".red() + ": mismatched types".bold();
+/// line += " in main.rs".normal();
+/// assert_eq!(line.len(), 41);
+/// assert_eq!(line.slice(5..12).spans, ["[E0308]".red()]);
+/// println!("{line}");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyledText {
+    /// The spans, in order.
+    pub spans: Vec<ColoredString>,
+}
+
+impl StyledText {
+    /// Creates a text without spans.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { spans: Vec::new() }
+    }
+
+    /// Appends a span.
+    pub fn push(&mut self, span: impl Into<ColoredString>) {
+        self.spans.push(span.into());
+    }
+
+    /// The length of the text in bytes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.spans.iter().map(|span| span.input.len()).sum()
+    }
+
+    /// Checks if the text is empty, i.e. if all its spans are.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.spans.iter().all(|span| span.input.is_empty())
+    }
+
+    /// Gets the number of columns the text takes in a terminal, see
+    /// [`display_width`](crate::display_width).
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.spans.iter().map(ColoredString::width).sum()
+    }
+
+    /// The text of the spans, without colors and style.
+    #[must_use]
+    pub fn plain(&self) -> String {
+        self.spans.iter().map(|span| span.input.as_str()).collect()
+    }
+
+    /// Gets the text in the byte `range`, keeping the colors and style of
+    /// each part. The spans left empty are removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't start and end on
+    /// char boundaries, like slicing a `str`.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let text = "one".red() + "two".blue();
+    /// assert_eq!(text.slice(2..4), "e".red() + "t".blue());
+    /// ```
+    #[must_use]
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let len = self.len();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end && end <= len,
+            "range {start}..{end} out of bounds of a text of length {len}"
+        );
+
+        let mut sliced = Self::new();
+        let mut offset = 0;
+        for span in &self.spans {
+            let span_end = offset + span.input.len();
+            let (from, to) = (start.max(offset), end.min(span_end));
+            if from < to {
+                sliced.spans.push(ColoredString {
+                    input: span.input[from - offset..to - offset].to_owned(),
+                    ..span.clone()
+                });
+            }
+            offset = span_end;
+        }
+        sliced
+    }
+
+    /// Splits the text around each occurrence of `separator`, which may be
+    /// spread across spans.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the separator is empty.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let text = "a,b".red() + ",c".blue();
+    /// let parts: Vec<_> = text.split(",").collect();
+    /// assert_eq!(parts, ["a".red().into(), "b".red().into(), "c".blue().into()]);
+    /// ```
+    pub fn split<'a>(&'a self, separator: &str) -> impl Iterator<Item = Self> + 'a {
+        assert!(!separator.is_empty(), "the separator cannot be empty");
+        let plain = self.plain();
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for (offset, _) in plain.match_indices(separator) {
+            ranges.push(start..offset);
+            start = offset + separator.len();
+        }
+        ranges.push(start..plain.len());
+        ranges.into_iter().map(|range| self.slice(range))
+    }
+
+    /// Splits the text into lines, like [`str::lines`]: the lines end with
+    /// `\n` or `\r\n`, which are removed, and the last line may not end
+    /// with one.
+    ///
+    /// ```rust
+    /// # use colored::*;
+    /// let text = "one\r\ntw".red() + "o\n".blue();
+    /// let lines: Vec<_> = text.lines().collect();
+    /// assert_eq!(lines, ["one".red().into(), "tw".red() + "o".blue()]);
+    /// ```
+    pub fn lines(&self) -> impl Iterator<Item = Self> + '_ {
+        let plain = self.plain();
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for line in plain.split_inclusive('\n') {
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            let content = content.strip_suffix('\r').unwrap_or(content);
+            ranges.push(start..start + content.len());
+            start += line.len();
+        }
+        ranges.into_iter().map(|range| self.slice(range))
+    }
+}
+
+/// Writes the spans with a [`Renderer`], only writing the attributes that
+/// change from one span to the next. The width of the formatter pads the
+/// whole text, using its [`width`](StyledText::width) in columns.
+impl fmt::Display for StyledText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_padded(f, self.width(), |f| {
+            let mut renderer = Renderer::new(f);
+            for span in &self.spans {
+                renderer.write(span)?;
+            }
+            renderer.finish().map(drop)
+        })
+    }
+}
+
+impl<T: Into<ColoredString>> From<T> for StyledText {
+    fn from(span: T) -> Self {
+        Self {
+            spans: vec![span.into()],
+        }
+    }
+}
+
+impl From<Vec<ColoredString>> for StyledText {
+    fn from(spans: Vec<ColoredString>) -> Self {
+        Self { spans }
+    }
+}
+
+impl FromIterator<ColoredString> for StyledText {
+    fn from_iter<I: IntoIterator<Item = ColoredString>>(iter: I) -> Self {
+        Self {
+            spans: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<ColoredString> for StyledText {
+    fn extend<I: IntoIterator<Item = ColoredString>>(&mut self, iter: I) {
+        self.spans.extend(iter);
+    }
+}
+
+impl IntoIterator for StyledText {
+    type Item = ColoredString;
+    type IntoIter = std::vec::IntoIter<ColoredString>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.spans.into_iter()
+    }
+}
+
+impl<T: Into<Self>> Add<T> for ColoredString {
+    type Output = StyledText;
+
+    fn add(self, rhs: T) -> StyledText {
+        StyledText {
+            spans: vec![self, rhs.into()],
+        }
+    }
+}
+
+impl Add<StyledText> for ColoredString {
+    type Output = StyledText;
+
+    fn add(self, mut rhs: StyledText) -> StyledText {
+        rhs.spans.insert(0, self);
+        rhs
+    }
+}
+
+impl<T: Into<ColoredString>> Add<T> for StyledText {
+    type Output = Self;
+
+    fn add(mut self, rhs: T) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl Add for StyledText {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl<T: Into<ColoredString>> AddAssign<T> for StyledText {
+    fn add_assign(&mut self, rhs: T) {
+        self.push(rhs);
+    }
+}
+
+impl AddAssign for StyledText {
+    fn add_assign(&mut self, rhs: Self) {
+        self.spans.extend(rhs.spans);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{control, Colorize};
+
+    fn diagnostic() -> StyledText {
+        "error".red().bold() + "[E0308]".red() + ": mismatched types"
+    }
+
+    #[test]
+    fn concatenation() {
+        let mut text = diagnostic();
+        assert_eq!(
+            text.spans,
+            [
+                "error".red().bold(),
+                "[E0308]".red(),
+                ": mismatched types".into()
+            ]
+        );
+        text += " here".blue() + "!";
+        text += String::from("?");
+        assert_eq!(text.plain(), "error[E0308]: mismatched types here!?");
+        assert_eq!(text.spans.len(), 6);
+
+        let prefixed = "=> ".green() + text.clone();
+        assert_eq!(prefixed.spans[0], "=> ".green());
+        assert_eq!(prefixed.spans[1..], text.spans);
+
+        let collected: StyledText = ["a".red(), "b".blue()].into_iter().collect();
+        let mut extended = StyledText::new();
+        extended.extend(["a".red(), "b".blue()]);
+        assert_eq!(collected, extended);
+        assert_eq!(collected.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn len_and_width() {
+        let text = diagnostic();
+        assert_eq!(text.len(), 30);
+        assert_eq!(text.width(), 30);
+        let wide = "日本".red() + "🦀".blue();
+        assert_eq!((wide.len(), wide.width()), (10, 6));
+        assert!(StyledText::new().is_empty());
+        assert!(("".red() + "").is_empty());
+        assert!(!wide.is_empty());
+    }
+
+    #[test]
+    fn slice() {
+        let text = diagnostic();
+        assert_eq!(text.slice(..), text);
+        assert_eq!(text.slice(3..3), StyledText::new());
+        assert_eq!(text.slice(3..=6), "or".red().bold() + "[E".red());
+        assert_eq!(text.slice(12..), ": mismatched types".into());
+    }
+
+    #[test]
+    #[should_panic = "out of bounds"]
+    fn slice_out_of_bounds() {
+        let _ = diagnostic().slice(10..40);
+    }
+
+    #[test]
+    fn split() {
+        let text = diagnostic();
+        let parts: Vec<_> = text.split("r").collect();
+        assert_eq!(
+            parts,
+            [
+                "e".red().bold().into(),
+                StyledText::new(),
+                "o".red().bold().into(),
+                "[E0308]".red() + ": mismatched types",
+            ]
+        );
+        let parts: Vec<_> = text.split("or[").map(|part| part.plain()).collect();
+        assert_eq!(parts, ["err", "E0308]: mismatched types"]);
+    }
+
+    #[test]
+    fn lines() {
+        let text = "one\ntwo".red() + "\r\n\nthree\n".blue();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "one".red().into(),
+                "two".red().into(),
+                StyledText::new(),
+                "three".blue().into(),
+            ]
+        );
+        assert_eq!(StyledText::new().lines().count(), 0);
+    }
+
+    #[cfg_attr(feature = "no-color", ignore)]
+    #[test]
+    fn display() {
+        let text = "a".red() + "b";
+        control::with_override(true, || {
+            assert_eq!(text.to_string(), "\x1B[31ma\x1B[39mb");
+            assert_eq!(format!("{text:>4}"), "  \x1B[31ma\x1B[39mb");
+            assert_eq!(format!("{text:-^5}"), "-\x1B[31ma\x1B[39mb--");
+        });
+        let plain = control::with_override(false, || format!("{text:4}|"));
+        assert_eq!(plain, "ab  |");
+    }
+}
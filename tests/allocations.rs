@@ -0,0 +1,96 @@
+#![cfg(not(feature = "no-color"))]
+// a global allocator can only be written with `unsafe`
+#![allow(unsafe_code)]
+
+use colored::{control, Color, ColorSupport, ColoredString, Colorize, Renderer, UnderlineStyle};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::Write;
+
+/// Counts the allocations made by the current thread, so that the other
+/// tests running at the same time aren't counted.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+/// Counts the allocations of writing `string` into a buffer large enough not
+/// to grow, once the detections done the first time have been made.
+fn render_allocations(string: &ColoredString) -> usize {
+    let mut buffer = String::with_capacity(64 * 1024);
+    write!(buffer, "{string}").unwrap();
+    buffer.clear();
+    allocations(|| write!(buffer, "{string}").unwrap())
+}
+
+/// Counts the allocations of writing `strings` with a [`Renderer`] into a
+/// buffer large enough not to grow.
+fn renderer_allocations(strings: &[ColoredString]) -> usize {
+    let mut buffer = String::with_capacity(64 * 1024);
+    allocations(|| {
+        let mut renderer = Renderer::new(&mut buffer);
+        for string in strings {
+            renderer.write(string).unwrap();
+        }
+        renderer.finish().unwrap();
+    })
+}
+
+#[test]
+fn rendering_does_not_allocate() {
+    let nested = format!("a {} b", "nested".blue().bold()).red().underline();
+    let strings = [
+        "plain".normal(),
+        "error".red().on_black().bold(),
+        "warning".color(Color::AnsiColor(208)),
+        "info".truecolor(0, 136, 255).italic(),
+        "typo"
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Color::Red),
+        "docs".hyperlink("https://docs.rs").blue(),
+        nested,
+        "gradient".gradient([Color::Red, Color::Blue]),
+    ];
+
+    control::with_override(true, || {
+        for support in [ColorSupport::Basic, ColorSupport::TrueColor] {
+            control::with_color_support(support, || {
+                control::with_extended_underlines(true, || {
+                    for string in &strings {
+                        assert_eq!(render_allocations(string), 0, "{string:?}");
+                    }
+                    // the renderer keeps a copy of the url of the hyperlink
+                    // being written
+                    assert_eq!(renderer_allocations(&strings), 1);
+                    assert_eq!(renderer_allocations(&strings[..5]), 0);
+                });
+            });
+        }
+    });
+}